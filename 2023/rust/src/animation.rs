@@ -0,0 +1,317 @@
+use crate::grid::{Grid, Point};
+use crate::image::{Image, Rgb};
+use std::collections::HashMap;
+use std::path::Path;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum AnimationError {
+    NoFrames,
+    TooManyColors,
+    FrameSize,
+}
+
+impl std::fmt::Display for AnimationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            AnimationError::NoFrames => write!(f, "No frames were recorded"),
+            AnimationError::TooManyColors => write!(f, "GIF frames are limited to 256 colours"),
+            AnimationError::FrameSize => write!(f, "Frames must share a size of at most 65535px"),
+        }
+    }
+}
+
+impl std::error::Error for AnimationError {}
+
+/// Collects successive grid states as frames of an animated GIF.
+pub struct Recorder {
+    scale: usize,
+    delay: u16,
+    skip: usize,
+    states: usize,
+    frames: Vec<Image>,
+}
+
+impl Recorder {
+    /// `delay` is the time each frame is shown, in milliseconds. Only every
+    /// `skip + 1`th recorded state becomes a frame.
+    pub fn new(scale: usize, delay: u32, skip: usize) -> Self {
+        Self {
+            scale,
+            delay: (delay / 10).min(u16::MAX as u32) as u16,
+            skip,
+            states: 0,
+            frames: vec![],
+        }
+    }
+
+    /// Captures `grid` unless the state is skipped, in which case the colour
+    /// mapping isn't evaluated at all.
+    pub fn record<T, F>(&mut self, grid: &Grid<T>, color: F)
+    where
+        F: Fn(Point, &T) -> Rgb,
+    {
+        if self.states.is_multiple_of(self.skip + 1) {
+            self.frames.push(Image::from_grid(grid, self.scale, color));
+        }
+        self.states += 1;
+    }
+
+    pub fn frames(&self) -> &[Image] {
+        &self.frames
+    }
+
+    pub fn to_gif(&self) -> Result<Vec<u8>, AnimationError> {
+        let first = self.frames.first().ok_or(AnimationError::NoFrames)?;
+        let (width, height) = (first.width(), first.height());
+        if width > u16::MAX as usize
+            || height > u16::MAX as usize
+            || self
+                .frames
+                .iter()
+                .any(|frame| (frame.width(), frame.height()) != (width, height))
+        {
+            return Err(AnimationError::FrameSize);
+        }
+
+        let mut palette: Vec<Rgb> = vec![];
+        let mut lookup: HashMap<Rgb, u8> = HashMap::new();
+        let frames = self
+            .frames
+            .iter()
+            .map(|frame| {
+                frame
+                    .pixels()
+                    .iter()
+                    .map(|pixel| match lookup.get(pixel) {
+                        Some(index) => Ok(*index),
+                        None if palette.len() < 256 => {
+                            let index = palette.len() as u8;
+                            palette.push(*pixel);
+                            lookup.insert(*pixel, index);
+                            Ok(index)
+                        }
+                        None => Err(AnimationError::TooManyColors),
+                    })
+                    .collect::<Result<Vec<_>, _>>()
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let bits = (usize::BITS - (palette.len() - 1).leading_zeros()).max(1) as u8;
+        palette.resize(1 << bits, Rgb::BLACK);
+
+        let mut res = b"GIF89a".to_vec();
+        res.extend((width as u16).to_le_bytes());
+        res.extend((height as u16).to_le_bytes());
+        res.extend([0xf0 | (bits - 1), 0, 0]);
+        res.extend(palette.iter().flat_map(|p| [p.0, p.1, p.2]));
+        // Loop forever.
+        res.extend(b"\x21\xff\x0bNETSCAPE2.0\x03\x01\x00\x00\x00");
+
+        for indices in frames {
+            res.extend([0x21, 0xf9, 0x04, 0x00]);
+            res.extend(self.delay.to_le_bytes());
+            res.extend([0x00, 0x00]);
+
+            res.extend([0x2c, 0, 0, 0, 0]);
+            res.extend((width as u16).to_le_bytes());
+            res.extend((height as u16).to_le_bytes());
+            res.push(0);
+
+            let min_code_size = bits.max(2);
+            res.push(min_code_size);
+            lzw_encode(&indices, min_code_size)
+                .chunks(255)
+                .for_each(|block| {
+                    res.push(block.len() as u8);
+                    res.extend(block);
+                });
+            res.push(0);
+        }
+
+        res.push(0x3b);
+        Ok(res)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        Ok(std::fs::write(path, self.to_gif()?)?)
+    }
+}
+
+#[derive(Default)]
+struct BitWriter {
+    bytes: Vec<u8>,
+    buffer: u32,
+    len: u8,
+}
+
+impl BitWriter {
+    fn write(&mut self, code: u16, size: u8) {
+        self.buffer |= (code as u32) << self.len;
+        self.len += size;
+        while self.len >= 8 {
+            self.bytes.push(self.buffer as u8);
+            self.buffer >>= 8;
+            self.len -= 8;
+        }
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.len > 0 {
+            self.bytes.push(self.buffer as u8);
+        }
+        self.bytes
+    }
+}
+
+/// Variable width LZW as used by GIF, emitting a clear code whenever the
+/// 12-bit code table fills up.
+fn lzw_encode(indices: &[u8], min_code_size: u8) -> Vec<u8> {
+    let clear = 1u16 << min_code_size;
+    let end = clear + 1;
+    let mut writer = BitWriter::default();
+    let mut table: HashMap<(u16, u8), u16> = HashMap::new();
+    let mut next = end + 1;
+    let mut size = min_code_size + 1;
+
+    writer.write(clear, size);
+    let mut indices = indices.iter();
+    if let Some(&first) = indices.next() {
+        let mut prefix = first as u16;
+        for &index in indices {
+            if let Some(&code) = table.get(&(prefix, index)) {
+                prefix = code;
+                continue;
+            }
+
+            writer.write(prefix, size);
+            if next == 4096 {
+                writer.write(clear, size);
+                table.clear();
+                next = end + 1;
+                size = min_code_size + 1;
+            } else {
+                if next >= 1 << size {
+                    size += 1;
+                }
+                table.insert((prefix, index), next);
+                next += 1;
+            }
+            prefix = index as u16;
+        }
+        writer.write(prefix, size);
+    }
+    writer.write(end, size);
+    writer.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lzw_decode(bytes: &[u8], min_code_size: u8) -> Vec<u8> {
+        let clear = 1u16 << min_code_size;
+        let end = clear + 1;
+        let mut table: Vec<Vec<u8>> = vec![];
+        let mut size = min_code_size + 1;
+        let mut prev: Option<u16> = None;
+        let (mut buffer, mut len, mut bytes) = (0u32, 0u8, bytes.iter());
+        let mut res = vec![];
+        loop {
+            while len < size {
+                buffer |= (*bytes.next().unwrap() as u32) << len;
+                len += 8;
+            }
+            let code = (buffer & ((1 << size) - 1)) as u16;
+            buffer >>= size;
+            len -= size;
+
+            if code == clear {
+                table = (0..clear).map(|i| vec![i as u8]).collect();
+                table.extend([vec![], vec![]]);
+                size = min_code_size + 1;
+                prev = None;
+                continue;
+            }
+            if code == end {
+                return res;
+            }
+
+            let entry = match prev {
+                None => table[code as usize].clone(),
+                Some(prev) => {
+                    let mut entry = table
+                        .get(code as usize)
+                        .cloned()
+                        .unwrap_or_else(|| table[prev as usize].clone());
+                    if code as usize == table.len() {
+                        entry.push(entry[0]);
+                    }
+                    let mut added = table[prev as usize].clone();
+                    added.push(entry[0]);
+                    table.push(added);
+                    entry
+                }
+            };
+            if table.len() == 1 << size && size < 12 {
+                size += 1;
+            }
+            res.extend(&entry);
+            prev = Some(code);
+        }
+    }
+
+    #[test]
+    fn test_lzw_roundtrip() {
+        let inputs = [
+            vec![],
+            vec![1],
+            vec![0, 1, 0, 1, 0, 1, 0, 1, 1, 1, 1],
+            (0..20_000).map(|i| ((i * 7919) % 13 % 4) as u8).collect(),
+            (0..50_000u32)
+                .map(|i| (i.wrapping_mul(2654435761) >> 27) as u8)
+                .collect(),
+        ];
+        for input in inputs {
+            let min_code_size = (*input.iter().max().unwrap_or(&0) as u32 + 1)
+                .next_power_of_two()
+                .trailing_zeros()
+                .max(2) as u8;
+            let encoded = lzw_encode(&input, min_code_size);
+            assert_eq!(lzw_decode(&encoded, min_code_size), input);
+        }
+    }
+
+    #[test]
+    fn test_recorder() {
+        let mut grid = Grid::filled(3, 2, false);
+        let mut recorder = Recorder::new(2, 100, 1);
+        for x in 0..3 {
+            grid.set(Point(x, 0), true);
+            recorder.record(&grid, |_, on| if *on { Rgb::WHITE } else { Rgb::BLACK });
+        }
+        assert_eq!(recorder.frames().len(), 2);
+        assert_eq!(recorder.frames()[1].pixel(5, 0), Some(Rgb::WHITE));
+
+        let gif = recorder.to_gif().unwrap();
+        assert!(gif.starts_with(b"GIF89a\x06\x00\x04\x00\xf0"));
+        assert_eq!(&gif[13..19], &[255, 255, 255, 0, 0, 0]);
+        assert_eq!(&gif[42..44], &[10, 0]);
+        assert_eq!(gif.last(), Some(&0x3b));
+    }
+
+    #[test]
+    fn test_recorder_errors() {
+        let recorder = Recorder::new(1, 0, 0);
+        assert_eq!(recorder.to_gif(), Err(AnimationError::NoFrames));
+
+        let mut recorder = Recorder::new(1, 0, 0);
+        let grid = Grid::new(vec![(0..=256u32).collect::<Vec<_>>()]).unwrap();
+        recorder.record(&grid, |_, n| Rgb((n / 2) as u8, (n % 2) as u8, 0));
+        assert_eq!(recorder.to_gif(), Err(AnimationError::TooManyColors));
+
+        let mut recorder = Recorder::new(1, 0, 0);
+        recorder.record(&Grid::filled(1, 1, ()), |_, _| Rgb::BLACK);
+        recorder.record(&Grid::filled(2, 1, ()), |_, _| Rgb::BLACK);
+        assert_eq!(recorder.to_gif(), Err(AnimationError::FrameSize));
+    }
+}
@@ -0,0 +1,357 @@
+use crate::grid::{Direction, Grid, Point};
+use std::ops::{BitAnd, BitOr, BitXor, Not};
+
+const WORD_BITS: usize = u64::BITS as usize;
+
+/// A grid of booleans packed into `u64` words. Every row starts on a fresh
+/// word, column `x` lives in bit `x % 64` of word `x / 64`, and padding bits
+/// past the width are always zero.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BitGrid {
+    width: usize,
+    height: usize,
+    stride: usize,
+    words: Vec<u64>,
+}
+
+impl BitGrid {
+    pub fn new(width: usize, height: usize) -> Self {
+        let stride = width.div_ceil(WORD_BITS);
+        Self {
+            width,
+            height,
+            stride,
+            words: vec![0; stride * height],
+        }
+    }
+
+    pub fn from_grid<T, F>(grid: &Grid<T>, predicate: F) -> Self
+    where
+        F: Fn(&T) -> bool,
+    {
+        let mut res = Self::new(grid.width(), grid.height());
+        grid.iter_cells()
+            .filter(|(_, cell)| predicate(cell))
+            .for_each(|(point, _)| {
+                res.insert(point);
+            });
+        res
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn in_bounds(&self, point: Point) -> bool {
+        point.0 < self.width && point.1 < self.height
+    }
+
+    fn locate(&self, point: Point) -> (usize, u64) {
+        (
+            point.1 * self.stride + point.0 / WORD_BITS,
+            1 << (point.0 % WORD_BITS),
+        )
+    }
+
+    /// Out of bounds points read as unset.
+    pub fn get(&self, point: Point) -> bool {
+        if !self.in_bounds(point) {
+            return false;
+        }
+        let (index, mask) = self.locate(point);
+        self.words[index] & mask != 0
+    }
+
+    /// Returns the previous value, or `None` when `point` is out of bounds.
+    pub fn set(&mut self, point: Point, value: bool) -> Option<bool> {
+        if !self.in_bounds(point) {
+            return None;
+        }
+        let (index, mask) = self.locate(point);
+        let previous = self.words[index] & mask != 0;
+        match value {
+            true => self.words[index] |= mask,
+            false => self.words[index] &= !mask,
+        }
+        Some(previous)
+    }
+
+    /// Sets `point`, returning whether it was newly set.
+    pub fn insert(&mut self, point: Point) -> bool {
+        self.set(point, true) == Some(false)
+    }
+
+    pub fn clear(&mut self) {
+        self.words.fill(0);
+    }
+
+    pub fn row(&self, y: usize) -> &[u64] {
+        &self.words[y * self.stride..(y + 1) * self.stride]
+    }
+
+    pub fn count_ones(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    pub fn row_count(&self, y: usize) -> usize {
+        self.row(y).iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    pub fn column_count(&self, x: usize) -> usize {
+        (0..self.height).filter(|&y| self.get(Point(x, y))).count()
+    }
+
+    pub fn row_xor(&self, a: usize, b: usize) -> impl Iterator<Item = u64> + '_ {
+        self.row(a).iter().zip(self.row(b)).map(|(a, b)| a ^ b)
+    }
+
+    /// Number of columns in which rows `a` and `b` differ.
+    pub fn row_distance(&self, a: usize, b: usize) -> usize {
+        self.row_xor(a, b).map(|w| w.count_ones() as usize).sum()
+    }
+
+    pub fn iter_ones(&self) -> impl Iterator<Item = Point> + '_ {
+        self.words
+            .iter()
+            .enumerate()
+            .flat_map(move |(index, &word)| {
+                let (y, offset) = (index / self.stride, index % self.stride * WORD_BITS);
+                let nonzero = |w: &u64| *w != 0;
+                std::iter::successors(Some(word).filter(nonzero), move |w| {
+                    Some(w & (w - 1)).filter(nonzero)
+                })
+                .map(move |w| Point(offset + w.trailing_zeros() as usize, y))
+            })
+    }
+
+    pub fn transpose(&self) -> Self {
+        let mut res = Self::new(self.height, self.width);
+        self.iter_ones().for_each(|Point(x, y)| {
+            res.insert(Point(y, x));
+        });
+        res
+    }
+
+    /// Moves every set cell `n` steps in `direction`, dropping the cells that
+    /// fall off the edge.
+    pub fn shift(&self, direction: Direction, n: usize) -> Self {
+        match direction {
+            Direction::Up => self.shift_rows(n, true),
+            Direction::Down => self.shift_rows(n, false),
+            Direction::Left => self.shift_columns(n, true),
+            Direction::Right => self.shift_columns(n, false),
+            Direction::UpLeft => self.shift(Direction::Up, n).shift(Direction::Left, n),
+            Direction::UpRight => self.shift(Direction::Up, n).shift(Direction::Right, n),
+            Direction::DownLeft => self.shift(Direction::Down, n).shift(Direction::Left, n),
+            Direction::DownRight => self.shift(Direction::Down, n).shift(Direction::Right, n),
+        }
+    }
+
+    fn shift_rows(&self, n: usize, up: bool) -> Self {
+        let mut res = Self::new(self.width, self.height);
+        let n = n.min(self.height) * self.stride;
+        let len = self.words.len() - n;
+        match up {
+            true => res.words[..len].copy_from_slice(&self.words[n..]),
+            false => res.words[n..].copy_from_slice(&self.words[..len]),
+        }
+        res
+    }
+
+    fn shift_columns(&self, n: usize, left: bool) -> Self {
+        let mut res = Self::new(self.width, self.height);
+        if n >= self.width {
+            return res;
+        }
+
+        let (words, bits) = (n / WORD_BITS, n % WORD_BITS);
+        let stride = self.stride;
+        for y in 0..self.height {
+            let row = self.row(y);
+            let word = |i: isize| match usize::try_from(i) {
+                Ok(i) if i < stride => row[i],
+                _ => 0,
+            };
+            (0..stride).for_each(|i| {
+                let i = i as isize;
+                let words = words as isize;
+                res.words[y * stride + i as usize] = match (left, bits) {
+                    (true, 0) => word(i + words),
+                    (true, _) => word(i + words) >> bits | word(i + words + 1) << (64 - bits),
+                    (false, 0) => word(i - words),
+                    (false, _) => word(i - words) << bits | word(i - words - 1) >> (64 - bits),
+                };
+            });
+        }
+        res.mask_padding();
+        res
+    }
+
+    fn mask_padding(&mut self) {
+        let bits = self.width % WORD_BITS;
+        if bits == 0 {
+            return;
+        }
+        let mask = (1u64 << bits) - 1;
+        self.words
+            .chunks_mut(self.stride)
+            .for_each(|row| row[row.len() - 1] &= mask);
+    }
+
+    fn zip_with<F>(&self, other: &BitGrid, f: F) -> BitGrid
+    where
+        F: Fn(u64, u64) -> u64,
+    {
+        assert_eq!(
+            (self.width, self.height),
+            (other.width, other.height),
+            "Bit grids must have the same size"
+        );
+        let words = self
+            .words
+            .iter()
+            .zip(&other.words)
+            .map(|(a, b)| f(*a, *b))
+            .collect();
+        BitGrid {
+            words,
+            ..self.clone()
+        }
+    }
+}
+
+impl BitAnd for &BitGrid {
+    type Output = BitGrid;
+
+    fn bitand(self, other: &BitGrid) -> BitGrid {
+        self.zip_with(other, |a, b| a & b)
+    }
+}
+
+impl BitOr for &BitGrid {
+    type Output = BitGrid;
+
+    fn bitor(self, other: &BitGrid) -> BitGrid {
+        self.zip_with(other, |a, b| a | b)
+    }
+}
+
+impl BitXor for &BitGrid {
+    type Output = BitGrid;
+
+    fn bitxor(self, other: &BitGrid) -> BitGrid {
+        self.zip_with(other, |a, b| a ^ b)
+    }
+}
+
+impl Not for &BitGrid {
+    type Output = BitGrid;
+
+    fn not(self) -> BitGrid {
+        let mut res = BitGrid {
+            words: self.words.iter().map(|w| !w).collect(),
+            ..self.clone()
+        };
+        res.mask_padding();
+        res
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(input: &str) -> BitGrid {
+        BitGrid::from_grid(&input.parse::<Grid<char>>().unwrap(), |c| *c == '#')
+    }
+
+    fn render(grid: &BitGrid) -> String {
+        (0..grid.height())
+            .map(|y| {
+                (0..grid.width())
+                    .map(|x| if grid.get(Point(x, y)) { '#' } else { '.' })
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn test_set_get() {
+        let mut grid = BitGrid::new(130, 3);
+        assert!(grid.insert(Point(0, 0)));
+        assert!(!grid.insert(Point(0, 0)));
+        assert_eq!(grid.set(Point(129, 2), true), Some(false));
+        assert_eq!(grid.set(Point(130, 2), true), None);
+        assert_eq!(grid.set(Point(64, 1), true), Some(false));
+        assert!(grid.get(Point(129, 2)));
+        assert!(!grid.get(Point(128, 2)));
+        assert!(!grid.get(Point(200, 200)));
+        assert_eq!(grid.row(1), &[0, 1, 0]);
+        assert_eq!(grid.count_ones(), 3);
+        assert_eq!(
+            grid.iter_ones().collect::<Vec<_>>(),
+            vec![Point(0, 0), Point(64, 1), Point(129, 2)]
+        );
+
+        grid.set(Point(0, 0), false);
+        assert_eq!(grid.count_ones(), 2);
+        grid.clear();
+        assert_eq!(grid.count_ones(), 0);
+    }
+
+    #[test]
+    fn test_counts() {
+        let grid = parse("#.#.\n.##.\n####");
+        assert_eq!(
+            (0..3).map(|y| grid.row_count(y)).collect::<Vec<_>>(),
+            vec![2, 2, 4]
+        );
+        assert_eq!(
+            (0..4).map(|x| grid.column_count(x)).collect::<Vec<_>>(),
+            vec![2, 2, 3, 1]
+        );
+        assert_eq!(grid.row_distance(0, 1), 2);
+        assert_eq!(grid.row_distance(0, 2), 2);
+        assert_eq!(grid.row_xor(1, 2).collect::<Vec<_>>(), vec![0b1001]);
+        assert_eq!(render(&grid.transpose()), "#.#\n.##\n###\n..#");
+    }
+
+    #[test]
+    fn test_shift() {
+        let grid = parse("#..#\n.#..\n..##");
+        assert_eq!(render(&grid.shift(Direction::Left, 1)), "..#.\n#...\n.##.");
+        assert_eq!(render(&grid.shift(Direction::Right, 1)), ".#..\n..#.\n...#");
+        assert_eq!(render(&grid.shift(Direction::Up, 1)), ".#..\n..##\n....");
+        assert_eq!(render(&grid.shift(Direction::Down, 2)), "....\n....\n#..#");
+        assert_eq!(
+            render(&grid.shift(Direction::DownRight, 1)),
+            "....\n.#..\n..#."
+        );
+        assert_eq!(grid.shift(Direction::Left, 4).count_ones(), 0);
+
+        let mut wide = BitGrid::new(150, 1);
+        wide.insert(Point(10, 0));
+        wide.insert(Point(149, 0));
+        let right = wide.shift(Direction::Right, 70);
+        assert_eq!(right.iter_ones().collect::<Vec<_>>(), vec![Point(80, 0)]);
+        let left = wide.shift(Direction::Left, 65);
+        assert_eq!(left.iter_ones().collect::<Vec<_>>(), vec![Point(84, 0)]);
+        assert_eq!(wide.shift(Direction::Left, 128).row(0), &[1 << 21, 0, 0]);
+    }
+
+    #[test]
+    fn test_boolean_ops() {
+        let a = parse("##..\n#.#.");
+        let b = parse("#.#.\n.##.");
+        assert_eq!(render(&(&a & &b)), "#...\n..#.");
+        assert_eq!(render(&(&a | &b)), "###.\n###.");
+        assert_eq!(render(&(&a ^ &b)), ".##.\n##..");
+        assert_eq!(render(&!&a), "..##\n.#.#");
+        assert_eq!((!&BitGrid::new(70, 2)).count_ones(), 140);
+    }
+}
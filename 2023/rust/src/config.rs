@@ -0,0 +1,454 @@
+use std::path::{Path, PathBuf};
+
+const CONFIG_FILE_NAME: &str = "aoc.toml";
+
+#[derive(Debug)]
+pub struct ConfigError(String);
+
+impl std::fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "Config error: {}", self.0)
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    Text,
+    Json,
+}
+
+impl std::str::FromStr for OutputFormat {
+    type Err = ConfigError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(OutputFormat::Text),
+            "json" => Ok(OutputFormat::Json),
+            _ => Err(ConfigError(format!("Unknown output format: {}", s))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Config {
+    pub year: u16,
+    /// Path to a day's puzzle input, `{day}` is replaced by the zero padded
+    /// day number and `{year}` by the configured year.
+    pub input: String,
+    pub format: OutputFormat,
+    pub timing: bool,
+    pub iterations: usize,
+    /// Where the adventofcode.com session token is kept, for tools that
+    /// fetch inputs. The runner itself only reads local input files.
+    pub session_file: Option<PathBuf>,
+    /// Where to write the day's image, if it draws one.
+    pub image: Option<PathBuf>,
+    /// Side length in pixels of one grid cell in images.
+    pub image_scale: usize,
+    /// Where to write an animated GIF of the day's simulation, if it has one.
+    pub animation: Option<PathBuf>,
+    /// Milliseconds each animation frame is shown for.
+    pub frame_delay: u32,
+    /// Number of states dropped between two animation frames.
+    pub frame_skip: usize,
+    /// Where to write a Graphviz DOT file of the day's graph, if it has one.
+    pub graph: Option<PathBuf>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            year: 2023,
+            input: "./input/day-{day}.txt".to_string(),
+            format: OutputFormat::Text,
+            timing: true,
+            iterations: 1,
+            session_file: None,
+            image: None,
+            image_scale: 4,
+            animation: None,
+            frame_delay: 100,
+            frame_skip: 0,
+            graph: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Value {
+    String(String),
+    Integer(i64),
+    Boolean(bool),
+}
+
+impl Value {
+    fn as_str(&self, key: &str) -> Result<&str, ConfigError> {
+        match self {
+            Value::String(s) => Ok(s),
+            _ => Err(ConfigError(format!("Expected a string for `{}`", key))),
+        }
+    }
+
+    fn as_integer(&self, key: &str) -> Result<i64, ConfigError> {
+        match self {
+            Value::Integer(n) => Ok(*n),
+            Value::String(s) => s
+                .parse::<i64>()
+                .map_err(|_| ConfigError(format!("Expected an integer for `{}`", key))),
+            _ => Err(ConfigError(format!("Expected an integer for `{}`", key))),
+        }
+    }
+
+    fn as_bool(&self, key: &str) -> Result<bool, ConfigError> {
+        match self {
+            Value::Boolean(b) => Ok(*b),
+            Value::String(s) if s == "true" || s == "1" => Ok(true),
+            Value::String(s) if s == "false" || s == "0" => Ok(false),
+            _ => Err(ConfigError(format!("Expected a boolean for `{}`", key))),
+        }
+    }
+}
+
+fn parse_value(s: &str) -> Option<Value> {
+    if let Some(s) = s.strip_prefix('"') {
+        let mut res = String::new();
+        let mut chars = s.chars();
+        loop {
+            match chars.next()? {
+                '"' => break,
+                '\\' => res.push(match chars.next()? {
+                    'n' => '\n',
+                    't' => '\t',
+                    c @ ('"' | '\\') => c,
+                    _ => return None,
+                }),
+                c => res.push(c),
+            }
+        }
+        match chars.as_str().trim() {
+            "" => Some(Value::String(res)),
+            rest if rest.starts_with('#') => Some(Value::String(res)),
+            _ => None,
+        }
+    } else {
+        let s = s.split('#').next()?.trim();
+        match s {
+            "true" => Some(Value::Boolean(true)),
+            "false" => Some(Value::Boolean(false)),
+            _ => s.replace('_', "").parse::<i64>().ok().map(Value::Integer),
+        }
+    }
+}
+
+/// Parses the subset of TOML used by `aoc.toml`: `[section]` headers and
+/// `key = value` pairs holding strings, integers or booleans. Keys inside a
+/// section are returned as `section.key`.
+fn parse_toml(input: &str) -> Result<Vec<(String, Value)>, ConfigError> {
+    let mut section = None;
+    input
+        .lines()
+        .enumerate()
+        .filter_map(|(index, line)| {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                return None;
+            }
+
+            if let Some(name) = line.strip_prefix('[') {
+                return match name
+                    .split('#')
+                    .next()
+                    .and_then(|n| n.trim().strip_suffix(']'))
+                {
+                    Some(name) => {
+                        section = Some(name.trim().to_string());
+                        None
+                    }
+                    None => Some(Err(ConfigError(format!(
+                        "Invalid section header on line {}",
+                        index + 1
+                    )))),
+                };
+            }
+
+            let pair = line.split_once('=').and_then(|(key, value)| {
+                let key = key.trim();
+                let value = parse_value(value.trim())?;
+                Some(match &section {
+                    Some(section) => (format!("{}.{}", section, key), value),
+                    None => (key.to_string(), value),
+                })
+            });
+            Some(pair.ok_or(ConfigError(format!("Invalid entry on line {}", index + 1))))
+        })
+        .collect()
+}
+
+fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), std::env::var_os("HOME")) {
+        (Some(rest), Some(home)) => Path::new(&home).join(rest),
+        _ => PathBuf::from(path),
+    }
+}
+
+impl Config {
+    fn set(&mut self, key: &str, value: &Value) -> Result<(), ConfigError> {
+        match key {
+            "year" => {
+                self.year = u16::try_from(value.as_integer(key)?)
+                    .map_err(|_| ConfigError(format!("Year out of range: {:?}", value)))?
+            }
+            "input" => self.input = value.as_str(key)?.to_string(),
+            "format" => self.format = value.as_str(key)?.parse()?,
+            "session_file" => self.session_file = Some(expand_home(value.as_str(key)?)),
+            "benchmark.timing" => self.timing = value.as_bool(key)?,
+            "benchmark.iterations" => {
+                self.iterations = usize::try_from(value.as_integer(key)?)
+                    .ok()
+                    .filter(|&n| n > 0)
+                    .ok_or(ConfigError("Iterations must be at least 1".to_string()))?
+            }
+            "image.path" => self.image = Some(expand_home(value.as_str(key)?)),
+            "image.scale" => {
+                self.image_scale = usize::try_from(value.as_integer(key)?)
+                    .ok()
+                    .filter(|&n| n > 0)
+                    .ok_or(ConfigError("Image scale must be at least 1".to_string()))?
+            }
+            "animation.path" => self.animation = Some(expand_home(value.as_str(key)?)),
+            "animation.delay" => {
+                self.frame_delay = u32::try_from(value.as_integer(key)?)
+                    .map_err(|_| ConfigError(format!("Frame delay out of range: {:?}", value)))?
+            }
+            "animation.skip" => {
+                self.frame_skip = usize::try_from(value.as_integer(key)?)
+                    .map_err(|_| ConfigError(format!("Frame skip out of range: {:?}", value)))?
+            }
+            "graph.path" => self.graph = Some(expand_home(value.as_str(key)?)),
+            _ => return Err(ConfigError(format!("Unknown key: {}", key))),
+        }
+
+        Ok(())
+    }
+
+    pub fn apply_toml(&mut self, input: &str) -> Result<(), ConfigError> {
+        parse_toml(input)?
+            .iter()
+            .try_for_each(|(key, value)| self.set(key, value))
+    }
+
+    /// Applies `AOC_*` overrides, e.g. `AOC_YEAR` or `AOC_BENCHMARK_ITERATIONS`.
+    pub fn apply_env<I>(&mut self, vars: I) -> Result<(), ConfigError>
+    where
+        I: IntoIterator<Item = (String, String)>,
+    {
+        vars.into_iter().try_for_each(|(name, value)| {
+            let key = match name.as_str() {
+                "AOC_YEAR" => "year",
+                "AOC_INPUT" => "input",
+                "AOC_FORMAT" => "format",
+                "AOC_SESSION_FILE" => "session_file",
+                "AOC_BENCHMARK_TIMING" => "benchmark.timing",
+                "AOC_BENCHMARK_ITERATIONS" => "benchmark.iterations",
+                "AOC_IMAGE_PATH" => "image.path",
+                "AOC_IMAGE_SCALE" => "image.scale",
+                "AOC_ANIMATION_PATH" => "animation.path",
+                "AOC_ANIMATION_DELAY" => "animation.delay",
+                "AOC_ANIMATION_SKIP" => "animation.skip",
+                "AOC_GRAPH_PATH" => "graph.path",
+                _ => return Ok(()),
+            };
+            self.set(key, &Value::String(value))
+        })
+    }
+
+    /// Looks for `aoc.toml` in the working directory, then in the user config
+    /// directory (`$XDG_CONFIG_HOME/aoc` or `~/.config/aoc`).
+    pub fn discover() -> Option<PathBuf> {
+        let local = PathBuf::from(CONFIG_FILE_NAME);
+        if local.is_file() {
+            return Some(local);
+        }
+
+        std::env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".config")))
+            .map(|dir| dir.join("aoc").join(CONFIG_FILE_NAME))
+            .filter(|path| path.is_file())
+    }
+
+    pub fn input_path(&self, day: usize) -> PathBuf {
+        PathBuf::from(
+            self.input
+                .replace("{day}", &format!("{:0>2}", day))
+                .replace("{year}", &self.year.to_string()),
+        )
+    }
+}
+
+pub struct Overrides(Vec<(String, Value)>);
+
+impl Overrides {
+    /// Splits command line flags out of `args`, returning the remaining
+    /// positional arguments alongside the collected overrides.
+    pub fn parse(args: &[String]) -> Result<(Vec<String>, Option<PathBuf>, Self), ConfigError> {
+        let mut positional = vec![];
+        let mut config_path = None;
+        let mut overrides = vec![];
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            let mut value = |flag: &str| {
+                args.next()
+                    .cloned()
+                    .ok_or(ConfigError(format!("Missing value for {}", flag)))
+            };
+            let (key, v) = match arg.as_str() {
+                "--config" => {
+                    config_path = Some(PathBuf::from(value(arg)?));
+                    continue;
+                }
+                "--year" => ("year", Value::String(value(arg)?)),
+                "--input" => ("input", Value::String(value(arg)?)),
+                "--format" => ("format", Value::String(value(arg)?)),
+                "--session-file" => ("session_file", Value::String(value(arg)?)),
+                "--iterations" => ("benchmark.iterations", Value::String(value(arg)?)),
+                "--timing" => ("benchmark.timing", Value::Boolean(true)),
+                "--no-timing" => ("benchmark.timing", Value::Boolean(false)),
+                "--image" => ("image.path", Value::String(value(arg)?)),
+                "--image-scale" => ("image.scale", Value::String(value(arg)?)),
+                "--animate" => ("animation.path", Value::String(value(arg)?)),
+                "--frame-delay" => ("animation.delay", Value::String(value(arg)?)),
+                "--frame-skip" => ("animation.skip", Value::String(value(arg)?)),
+                "--graph" => ("graph.path", Value::String(value(arg)?)),
+                flag if flag.starts_with("--") => {
+                    return Err(ConfigError(format!("Unknown flag: {}", flag)))
+                }
+                _ => {
+                    positional.push(arg.clone());
+                    continue;
+                }
+            };
+            overrides.push((key.to_string(), v));
+        }
+
+        Ok((positional, config_path, Self(overrides)))
+    }
+
+    pub fn apply(&self, config: &mut Config) -> Result<(), ConfigError> {
+        self.0
+            .iter()
+            .try_for_each(|(key, value)| config.set(key, value))
+    }
+}
+
+/// Builds the runner configuration. Later sources win: defaults, then the
+/// config file, then `AOC_*` environment variables, then command line flags.
+pub fn load(args: &[String]) -> Result<(Vec<String>, Config), Box<dyn std::error::Error>> {
+    let (positional, config_path, overrides) = Overrides::parse(args)?;
+    let config_path = config_path
+        .or_else(|| std::env::var_os("AOC_CONFIG").map(PathBuf::from))
+        .or_else(Config::discover);
+
+    let mut config = Config::default();
+    if let Some(path) = config_path {
+        let contents = std::fs::read_to_string(&path)
+            .map_err(|e| ConfigError(format!("{}: {}", path.display(), e)))?;
+        config.apply_toml(&contents)?;
+    }
+    config.apply_env(std::env::vars())?;
+    overrides.apply(&mut config)?;
+
+    Ok((positional, config))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_CONFIG: &str = r#"
+# Runner defaults
+year = 2023
+input = "../inputs/{year}/day{day}.txt" # relative to the crate
+format = "json"
+
+[benchmark]
+timing = false
+iterations = 10
+"#;
+
+    #[test]
+    fn test_parse_config() {
+        let mut config = Config::default();
+        config.apply_toml(SAMPLE_CONFIG).unwrap();
+        assert_eq!(config.format, OutputFormat::Json);
+        assert!(!config.timing);
+        assert_eq!(config.iterations, 10);
+        assert_eq!(
+            config.input_path(7),
+            PathBuf::from("../inputs/2023/day07.txt")
+        );
+    }
+
+    #[test]
+    fn test_invalid_config() {
+        let mut config = Config::default();
+        assert!(config.apply_toml("year = \"soon\"").is_err());
+        assert!(config.apply_toml("colour = true").is_err());
+        assert!(config.apply_toml("[benchmark\niterations = 2").is_err());
+        assert!(config.apply_toml("[benchmark]\niterations = 0").is_err());
+    }
+
+    #[test]
+    fn test_overrides() {
+        let mut config = Config::default();
+        config.apply_toml(SAMPLE_CONFIG).unwrap();
+        config
+            .apply_env(vec![
+                ("AOC_FORMAT".to_string(), "text".to_string()),
+                ("AOC_BENCHMARK_ITERATIONS".to_string(), "3".to_string()),
+                ("PATH".to_string(), "/bin".to_string()),
+            ])
+            .unwrap();
+        assert_eq!(config.format, OutputFormat::Text);
+        assert_eq!(config.iterations, 3);
+
+        let args = ["aoc", "12", "--iterations", "5", "--timing"].map(String::from);
+        let (positional, config_path, overrides) = Overrides::parse(&args).unwrap();
+        overrides.apply(&mut config).unwrap();
+        assert_eq!(positional, vec!["aoc", "12"]);
+        assert_eq!(config_path, None);
+        assert_eq!(config.iterations, 5);
+        assert!(config.timing);
+
+        let args = ["aoc", "16", "--image", "beams.png", "--image-scale", "8"].map(String::from);
+        let (positional, _, overrides) = Overrides::parse(&args).unwrap();
+        overrides.apply(&mut config).unwrap();
+        assert_eq!(positional, vec!["aoc", "16"]);
+        assert_eq!(config.image, Some(PathBuf::from("beams.png")));
+        assert_eq!(config.image_scale, 8);
+        assert!(Overrides::parse(&["--image-scale", "0"].map(String::from))
+            .unwrap()
+            .2
+            .apply(&mut config)
+            .is_err());
+
+        let args = ["aoc", "14", "--animate", "spin.gif", "--frame-skip", "3"].map(String::from);
+        let (_, _, overrides) = Overrides::parse(&args).unwrap();
+        overrides.apply(&mut config).unwrap();
+        assert_eq!(config.animation, Some(PathBuf::from("spin.gif")));
+        assert_eq!(config.frame_delay, 100);
+        assert_eq!(config.frame_skip, 3);
+        assert!(config.apply_toml("[animation]\nskip = -1").is_err());
+
+        let args = ["aoc", "19", "--graph", "workflows.dot"].map(String::from);
+        let (_, _, overrides) = Overrides::parse(&args).unwrap();
+        overrides.apply(&mut config).unwrap();
+        assert_eq!(config.graph, Some(PathBuf::from("workflows.dot")));
+        config
+            .apply_env([("AOC_GRAPH_PATH".to_string(), "network.dot".to_string())])
+            .unwrap();
+        assert_eq!(config.graph, Some(PathBuf::from("network.dot")));
+    }
+}
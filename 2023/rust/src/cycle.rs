@@ -0,0 +1,178 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+
+/// The states of an eventually periodic sequence repeat with period `length`
+/// from iteration `start` on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cycle {
+    pub start: usize,
+    pub length: usize,
+}
+
+impl Cycle {
+    /// Earliest iteration holding the same state as iteration `n`.
+    pub fn reduce(&self, n: usize) -> usize {
+        match n.checked_sub(self.start) {
+            Some(offset) => self.start + offset % self.length,
+            None => n,
+        }
+    }
+}
+
+fn hash_state<S: Hash>(state: &S) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    state.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Applies `step` to `state` `n` times, remembering every state seen so the
+/// run can jump ahead as soon as one repeats. States are indexed by a 64-bit
+/// hash and only compared in full when hashes collide.
+pub fn fast_forward<S, F>(state: S, n: usize, mut step: F) -> S
+where
+    S: Hash + Eq + Clone,
+    F: FnMut(&mut S),
+{
+    let mut history: Vec<S> = vec![];
+    let mut seen: HashMap<u64, Vec<usize>> = HashMap::new();
+    let mut state = state;
+    for iteration in 0..n {
+        let indices = seen.entry(hash_state(&state)).or_default();
+        if let Some(&start) = indices.iter().find(|&&i| history[i] == state) {
+            let cycle = Cycle {
+                start,
+                length: iteration - start,
+            };
+            return history.swap_remove(cycle.reduce(n));
+        }
+
+        indices.push(iteration);
+        history.push(state.clone());
+        step(&mut state);
+    }
+
+    state
+}
+
+/// Finds the cycle reached from `initial` with Brent's algorithm, which
+/// keeps only two states alive at a time. Never returns if the sequence
+/// doesn't repeat.
+pub fn brent<S, F>(initial: &S, mut step: F) -> Cycle
+where
+    S: PartialEq + Clone,
+    F: FnMut(&mut S),
+{
+    let mut power = 1;
+    let mut length = 1;
+    let mut tortoise = initial.clone();
+    let mut hare = initial.clone();
+    step(&mut hare);
+    while tortoise != hare {
+        if power == length {
+            tortoise = hare.clone();
+            power *= 2;
+            length = 0;
+        }
+        step(&mut hare);
+        length += 1;
+    }
+
+    let mut tortoise = initial.clone();
+    let mut hare = initial.clone();
+    (0..length).for_each(|_| step(&mut hare));
+    let mut start = 0;
+    while tortoise != hare {
+        step(&mut tortoise);
+        step(&mut hare);
+        start += 1;
+    }
+
+    Cycle { start, length }
+}
+
+/// Like `fast_forward`, but finds the cycle with `brent` instead of storing
+/// every state, then replays at most `start + length` steps.
+pub fn fast_forward_brent<S, F>(state: S, n: usize, mut step: F) -> S
+where
+    S: PartialEq + Clone,
+    F: FnMut(&mut S),
+{
+    let cycle = brent(&state, &mut step);
+    let mut state = state;
+    (0..cycle.reduce(n)).for_each(|_| step(&mut state));
+    state
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 3, 10, 17, 17, ... under x -> x^2 + 1 mod 21
+    fn step(x: &mut u64) {
+        *x = (*x * *x + 1) % 21;
+    }
+
+    #[test]
+    fn test_reduce() {
+        let cycle = Cycle {
+            start: 2,
+            length: 3,
+        };
+        assert_eq!(cycle.reduce(1), 1);
+        assert_eq!(cycle.reduce(2), 2);
+        assert_eq!(cycle.reduce(5), 2);
+        assert_eq!(cycle.reduce(1_000_000_000), 4);
+    }
+
+    #[test]
+    fn test_brent() {
+        let mut states = vec![3];
+        (0..8).for_each(|_| {
+            let mut x = *states.last().unwrap();
+            step(&mut x);
+            states.push(x);
+        });
+        assert_eq!(states, vec![3, 10, 17, 17, 17, 17, 17, 17, 17]);
+        assert_eq!(
+            brent(&3, step),
+            Cycle {
+                start: 2,
+                length: 1
+            }
+        );
+
+        let mut counter = |x: &mut u64| *x = (*x + 1) % 7;
+        assert_eq!(
+            brent(&0, &mut counter),
+            Cycle {
+                start: 0,
+                length: 7
+            }
+        );
+        let mut tail = |x: &mut u64| *x = if *x > 10 { *x - 1 } else { (*x + 1) % 4 };
+        assert_eq!(
+            brent(&15, &mut tail),
+            Cycle {
+                start: 6,
+                length: 4
+            }
+        );
+    }
+
+    #[test]
+    fn test_fast_forward() {
+        let tail = |x: &mut u64| *x = if *x > 10 { *x - 1 } else { (*x + 1) % 4 };
+        let expected = |n: usize| {
+            let mut x = 15;
+            (0..n).for_each(|_| tail(&mut x));
+            x
+        };
+        for n in [0, 3, 5, 6, 9, 100, 1001] {
+            assert_eq!(fast_forward(15, n, tail), expected(n), "n = {}", n);
+            assert_eq!(fast_forward_brent(15, n, tail), expected(n), "n = {}", n);
+        }
+        assert_eq!(fast_forward(15, 1_000_000_000_000, tail), 1);
+        assert_eq!(fast_forward_brent(15, 1_000_000_000_000, tail), 1);
+    }
+}
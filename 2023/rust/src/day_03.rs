@@ -1,3 +1,6 @@
+use crate::grid::{Connectivity, Grid, GridError, Point};
+use std::collections::{BTreeMap, BTreeSet};
+
 pub struct Puzzle {
     puzzle_input: String,
 }
@@ -10,95 +13,65 @@ impl Puzzle {
     }
 }
 
-struct Board(Vec<Vec<char>>);
-
-impl Board {
-    fn create(board: Vec<Vec<char>>) -> Self {
-        Self(board)
-    }
-}
-
 #[derive(Clone, Copy, Debug, PartialEq)]
-struct Position {
-    x: usize,
-    y: usize,
-}
-
-#[derive(Clone, Copy, Debug, PartialEq)]
-struct SchematicValue<T> {
-    value: T,
-    pos: Position,
+struct SchematicNumber {
+    value: usize,
+    pos: Point,
     length: usize,
 }
 
-impl<T> SchematicValue<T> {
-    fn is_adjacent<A>(&self, other: &SchematicValue<A>) -> bool {
-        let adjacent_x = if self.pos.x >= other.pos.x {
-            self.pos.x - other.pos.x <= other.length
-        } else {
-            other.pos.x - self.pos.x <= self.length
-        };
-
-        let adjacent_y = if self.pos.y >= other.pos.y {
-            self.pos.y - other.pos.y <= 1
-        } else {
-            other.pos.y - self.pos.y <= 1
-        };
-
-        adjacent_x && adjacent_y
-    }
-}
-
-type SchematicNumber = SchematicValue<usize>;
-type SchematicSymbol = SchematicValue<char>;
-
 impl SchematicNumber {
     fn create(value: usize, x: usize, y: usize, length: usize) -> Self {
         Self {
             value,
-            pos: Position { x, y },
+            pos: Point(x, y),
             length,
         }
     }
 }
 
-impl SchematicSymbol {
-    fn create(value: char, x: usize, y: usize) -> Self {
-        Self {
-            value,
-            pos: Position { x, y },
-            length: 1,
-        }
-    }
+fn is_symbol(c: &char) -> bool {
+    !c.is_ascii_digit() && *c != '.'
 }
 
 #[derive(Debug)]
 struct EngineSchematic {
-    symbols: Vec<SchematicSymbol>,
-    schematic_numbers: Vec<SchematicValue<usize>>,
+    board: Grid<char>,
+    schematic_numbers: Vec<SchematicNumber>,
 }
 
-fn parse_board(puzzle_input: &str) -> EngineSchematic {
-    let board = puzzle_input
-        .lines()
-        .map(|line| line.chars().collect::<Vec<char>>())
-        .collect::<Vec<Vec<char>>>();
-    let board = Board::create(board);
+impl EngineSchematic {
+    fn adjacent_symbols(&self, number: &SchematicNumber) -> BTreeSet<Point> {
+        (number.pos.0..number.pos.0 + number.length)
+            .flat_map(|x| {
+                self.board
+                    .neighbors(Point(x, number.pos.1), Connectivity::Eight)
+            })
+            .filter(|(_, c)| is_symbol(c))
+            .map(|(point, _)| point)
+            .collect()
+    }
+}
+
+fn parse_board(puzzle_input: &str) -> Result<EngineSchematic, GridError> {
+    let board = puzzle_input.parse::<Grid<char>>()?;
 
-    let mut symbols: Vec<SchematicSymbol> = vec![];
     let mut schematic_numbers: Vec<SchematicNumber> = vec![];
 
-    board.0.iter().enumerate().for_each(|(y, line)| {
+    (0..board.height()).for_each(|y| {
         let mut current_number: Option<SchematicNumber> = None;
-        line.iter()
+        board
+            .row(y)
+            .unwrap_or_default()
+            .iter()
             .enumerate()
-            .for_each(|(x, c)| match c.to_string().parse::<usize>() {
-                Ok(d) => {
+            .for_each(|(x, c)| match c.to_digit(10) {
+                Some(d) => {
                     if let Some(s) = &mut current_number {
                         s.length = s.length + 1;
-                        s.value = s.value * 10 + d;
+                        s.value = s.value * 10 + d as usize;
                     } else {
-                        current_number = Some(SchematicNumber::create(d, x, y, 1));
+                        current_number = Some(SchematicNumber::create(d as usize, x, y, 1));
                     }
                 }
                 _ => {
@@ -106,10 +79,6 @@ fn parse_board(puzzle_input: &str) -> EngineSchematic {
                         schematic_numbers.push(*s);
                     }
                     current_number = None;
-
-                    if *c != '.' {
-                        symbols.push(SchematicSymbol::create(*c, x, y))
-                    }
                 }
             });
         if let Some(s) = &current_number {
@@ -117,51 +86,49 @@ fn parse_board(puzzle_input: &str) -> EngineSchematic {
         }
     });
 
-    return EngineSchematic {
-        symbols,
+    Ok(EngineSchematic {
+        board,
         schematic_numbers,
-    };
+    })
 }
 
 impl super::Puzzle for Puzzle {
     fn run_part_one(&self) -> Result<super::AOCResult, Box<dyn std::error::Error>> {
-        let engine_schematic = parse_board(&self.puzzle_input);
+        let engine_schematic = parse_board(&self.puzzle_input)?;
 
         let res = engine_schematic
             .schematic_numbers
             .iter()
-            .filter(|s| {
-                engine_schematic
-                    .symbols
-                    .iter()
-                    .any(|symbol| s.is_adjacent(symbol))
-            })
+            .filter(|s| !engine_schematic.adjacent_symbols(s).is_empty())
             .fold(0, |acc, s| acc + s.value);
 
         Ok(super::AOCResult::USize(res))
     }
 
     fn run_part_two(&self) -> Result<super::AOCResult, Box<dyn std::error::Error>> {
-        let engine_schematic = parse_board(&self.puzzle_input);
+        let engine_schematic = parse_board(&self.puzzle_input)?;
 
-        let res = engine_schematic
-            .symbols
+        let mut symbol_neighbors: BTreeMap<Point, Vec<usize>> = BTreeMap::new();
+        engine_schematic
+            .schematic_numbers
             .iter()
-            .filter_map(|symbol| {
-                let neighbors = engine_schematic
-                    .schematic_numbers
-                    .iter()
-                    .filter_map(|number| {
-                        if symbol.is_adjacent(&number) {
-                            Some(number.value)
-                        } else {
-                            None
-                        }
+            .for_each(|number| {
+                engine_schematic
+                    .adjacent_symbols(number)
+                    .into_iter()
+                    .for_each(|symbol| {
+                        symbol_neighbors
+                            .entry(symbol)
+                            .or_default()
+                            .push(number.value)
                     })
-                    .collect::<Vec<usize>>();
+            });
 
+        let res = symbol_neighbors
+            .values()
+            .filter_map(|neighbors| {
                 if neighbors.len() == 2 {
-                    Some(neighbors.get(0).unwrap() * neighbors.get(1).unwrap())
+                    Some(neighbors[0] * neighbors[1])
                 } else {
                     None
                 }
@@ -178,13 +145,13 @@ mod tests {
 
     #[test]
     fn test_parse() {
-        let schematic = parse_board(&"12..34\n*.123*\n...#45");
+        let schematic = parse_board(&"12..34\n*.123*\n...#45").unwrap();
         assert_eq!(schematic.schematic_numbers.len(), 4);
         assert_eq!(
             *schematic.schematic_numbers.get(0).unwrap(),
             SchematicNumber {
                 value: 12,
-                pos: Position { x: 0, y: 0 },
+                pos: Point(0, 0),
                 length: 2
             }
         );
@@ -192,7 +159,7 @@ mod tests {
             *schematic.schematic_numbers.get(1).unwrap(),
             SchematicNumber {
                 value: 34,
-                pos: Position { x: 4, y: 0 },
+                pos: Point(4, 0),
                 length: 2
             }
         );
@@ -200,30 +167,38 @@ mod tests {
             *schematic.schematic_numbers.get(2).unwrap(),
             SchematicNumber {
                 value: 123,
-                pos: Position { x: 2, y: 1 },
+                pos: Point(2, 1),
                 length: 3
             }
         );
     }
 
     #[test]
-    fn test_is_adjacent() {
-        let a = SchematicNumber::create(123, 5, 5, 3);
-
-        let b = SchematicSymbol::create('*', 5, 4);
-        assert!(a.is_adjacent(&b));
-        assert!(b.is_adjacent(&a));
-
-        let b = SchematicSymbol::create('*', 4, 5);
-        assert!(a.is_adjacent(&b));
-        assert!(b.is_adjacent(&a));
+    fn test_adjacent_symbols() {
+        let schematic = parse_board(
+            "......
+.*..*.
+.*123.
+......
+....*.",
+        )
+        .unwrap();
+        let number = SchematicNumber::create(123, 2, 2, 3);
+        assert_eq!(schematic.schematic_numbers, vec![number]);
+        assert_eq!(
+            schematic.adjacent_symbols(&number),
+            BTreeSet::from([Point(1, 1), Point(4, 1), Point(1, 2)])
+        );
 
-        let b = SchematicSymbol::create('*', 4, 4);
-        assert!(a.is_adjacent(&b));
-        assert!(b.is_adjacent(&a));
+        let schematic = parse_board("......\n.123..\n....*.").unwrap();
+        assert_eq!(
+            schematic.adjacent_symbols(&schematic.schematic_numbers[0]),
+            BTreeSet::from([Point(4, 2)])
+        );
 
-        let b = SchematicSymbol::create('*', 8, 6);
-        assert!(a.is_adjacent(&b));
-        assert!(b.is_adjacent(&a));
+        let schematic = parse_board("*.....\n..123.\n......").unwrap();
+        assert!(schematic
+            .adjacent_symbols(&schematic.schematic_numbers[0])
+            .is_empty());
     }
 }
@@ -1,3 +1,4 @@
+use crate::interval::{Interval, IntervalSet};
 use std::str::FromStr;
 
 #[derive(Debug)]
@@ -90,6 +91,14 @@ impl Rule {
             None
         }
     }
+
+    fn source(&self) -> Interval {
+        Interval::with_length(self.source_start as i64, self.length as i64)
+    }
+
+    fn offset(&self) -> i64 {
+        self.destination_start as i64 - self.source_start as i64
+    }
 }
 
 struct ParseRuleError;
@@ -162,6 +171,18 @@ impl Almanac {
 
         res
     }
+
+    /// Pushes whole ranges of seeds through every stage at once, splitting
+    /// them wherever a rule starts or ends.
+    fn locations(&self, seeds: &IntervalSet) -> IntervalSet {
+        self.0.iter().fold(seeds.clone(), |numbers, rules| {
+            let pieces = rules
+                .iter()
+                .map(|rule| (rule.source(), rule.offset()))
+                .collect::<Vec<_>>();
+            numbers.map_offsets(&pieces)
+        })
+    }
 }
 
 #[derive(Debug)]
@@ -225,25 +246,9 @@ impl super::Puzzle for Puzzle {
         Ok(super::AOCResult::USize(min_location))
     }
     fn run_part_two(&self) -> Result<super::AOCResult, Box<dyn std::error::Error>> {
-        let min = self
-            .seeds
-            .windows(2)
-            .step_by(2)
-            .filter_map(|window| {
-                if let [start, length] = &window {
-                    let range = *start..(*start + *length);
-                    let all_seed_info = range
-                        .map(|s| self.almanac.seed_info(s)[6])
-                        .collect::<Vec<usize>>();
-                    let min_location = all_seed_info.iter().min().unwrap();
-                    Some(*min_location)
-                } else {
-                    None
-                }
-            })
-            .min();
+        let min = self.almanac.locations(&seed_ranges(&self.seeds)).min();
 
-        min.map(|r| super::AOCResult::USize(r))
+        min.map(|r| super::AOCResult::USize(r as usize))
             .ok_or(Box::new(std::io::Error::new(
                 std::io::ErrorKind::Other,
                 "No min found",
@@ -251,6 +256,13 @@ impl super::Puzzle for Puzzle {
     }
 }
 
+fn seed_ranges(seeds: &[usize]) -> IntervalSet {
+    seeds
+        .chunks_exact(2)
+        .map(|pair| Interval::with_length(pair[0] as i64, pair[1] as i64))
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -299,25 +311,15 @@ humidity-to-location map:
         let min_location = all_seed_info.iter().min_by(|x, y| x[6].cmp(&y[6])).unwrap();
         assert_eq!(min_location[6], 35);
 
-        let min = seeds
-            .windows(2)
-            .step_by(2)
-            .filter_map(|window| {
-                if let [start, length] = &window {
-                    let range = *start..(*start + *length);
-                    dbg!(&range);
-                    let all_seed_info = range
-                        .map(|s| almanac.seed_info(s)[6])
-                        .collect::<Vec<usize>>();
-                    let min_location = all_seed_info.iter().min().unwrap();
-                    Some(*min_location)
-                } else {
-                    None
-                }
-            })
-            .min();
+        let locations = almanac.locations(&seed_ranges(&seeds));
+        assert_eq!(locations.min(), Some(46));
 
-        assert_eq!(min.unwrap(), 35);
+        let brute_force = seeds
+            .chunks_exact(2)
+            .flat_map(|pair| pair[0]..pair[0] + pair[1])
+            .map(|s| Interval::with_length(almanac.seed_info(s)[6] as i64, 1))
+            .collect::<IntervalSet>();
+        assert_eq!(locations, brute_force);
     }
 
     #[test]
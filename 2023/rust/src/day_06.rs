@@ -1,3 +1,5 @@
+use std::ops::RangeInclusive;
+
 pub struct Puzzle(String);
 
 impl Puzzle {
@@ -14,15 +16,47 @@ impl Puzzle {
     }
 }
 
-fn ways_to_win(time: u128, record: u128) -> u128 {
-    let s = f64::sqrt((time.pow(2) - (4 * record)) as f64);
+/// Hold times that beat `record`. Holding for `h` travels `h * (time - h)`,
+/// which beats the record exactly when `(2h - time)^2 < time^2 - 4 * record`,
+/// so the bounds come from an integer square root rather than floats.
+pub fn winning_hold_times(time: u128, record: u128) -> Option<RangeInclusive<u128>> {
+    let (Some(square), Some(quadrupled)) = (time.checked_mul(time), record.checked_mul(4)) else {
+        return search_hold_times(time, record);
+    };
+    let discriminant = square.checked_sub(quadrupled)?;
+    if discriminant == 0 {
+        return None;
+    }
 
-    let max = (time as f64 + s) / 2.;
-    let max = max.ceil() as u128 - 1;
-    let min = (time as f64 - s) / 2.;
-    let min = min.floor() as u128 + 1;
+    // Largest distance from time / 2, doubled, that still strictly wins.
+    let spread = (discriminant - 1).isqrt();
+    let min = (time - spread).div_ceil(2);
+    let max = (time + spread) / 2;
+    (min <= max).then_some(min..=max)
+}
 
-    max - min + 1
+/// Slower fallback for races too long to square: distance grows with the
+/// hold time up to `time / 2`, so the first winning hold is found by
+/// bisection, and the last one mirrors it.
+fn search_hold_times(time: u128, record: u128) -> Option<RangeInclusive<u128>> {
+    // A distance past u128::MAX beats any record.
+    let wins = |hold: u128| hold.checked_mul(time - hold).is_none_or(|d| d > record);
+    let (mut lo, mut hi) = (0, time / 2);
+    if !wins(hi) {
+        return None;
+    }
+    while lo < hi {
+        let mid = lo + (hi - lo) / 2;
+        match wins(mid) {
+            true => hi = mid,
+            false => lo = mid + 1,
+        }
+    }
+    Some(lo..=time - lo)
+}
+
+fn ways_to_win(time: u128, record: u128) -> u128 {
+    winning_hold_times(time, record).map_or(0, |holds| holds.end() - holds.start() + 1)
 }
 
 #[derive(Debug)]
@@ -107,4 +141,56 @@ mod tests {
         assert_eq!(ways_to_win(15, 40), 8);
         assert_eq!(ways_to_win(30, 200), 9);
     }
+
+    #[test]
+    fn test_winning_hold_times() {
+        assert_eq!(winning_hold_times(7, 9), Some(2..=5));
+        assert_eq!(winning_hold_times(71530, 940200), Some(14..=71516));
+        assert_eq!(ways_to_win(71530, 940200), 71503);
+
+        // 10^2 - 4 * 21 = 16: holding 3 or 7 only ties the record.
+        assert_eq!(winning_hold_times(10, 21), Some(4..=6));
+        // 10^2 - 4 * 24 = 4: only the midpoint wins.
+        assert_eq!(winning_hold_times(10, 24), Some(5..=5));
+        // 10^2 - 4 * 25 = 0: the best hold ties.
+        assert_eq!(winning_hold_times(10, 25), None);
+        assert_eq!(winning_hold_times(10, 30), None);
+        assert_eq!(ways_to_win(10, 25), 0);
+        assert_eq!(winning_hold_times(11, 0), Some(1..=10));
+    }
+
+    #[test]
+    fn test_large_race() {
+        // Beyond f64 precision, the record sits one short of the best distance.
+        let time = 1u128 << 60;
+        let best = (time / 2) * (time / 2);
+        assert_eq!(
+            winning_hold_times(time, best - 1),
+            Some(time / 2..=time / 2)
+        );
+        assert_eq!(winning_hold_times(time, best), None);
+        assert_eq!(ways_to_win(time + 1, best + time / 2 - 1), 2);
+    }
+
+    #[test]
+    fn test_unsquarable_race() {
+        for (time, record) in [
+            (7, 9),
+            (10, 21),
+            (10, 24),
+            (10, 25),
+            (11, 0),
+            (71530, 940200),
+        ] {
+            assert_eq!(
+                search_hold_times(time, record),
+                winning_hold_times(time, record)
+            );
+        }
+
+        let time = u128::MAX - 1;
+        assert_eq!(winning_hold_times(time, 0), Some(1..=time - 1));
+        assert_eq!(winning_hold_times(time, u128::MAX), Some(2..=time - 2));
+        assert_eq!(winning_hold_times(1 << 64, u128::MAX), None);
+    }
 }
@@ -1,95 +1,129 @@
+use crate::graph::Graph;
+use crate::grid::Direction;
+use crate::interner::Interner;
+use crate::number_theory::crt;
 use std::collections::HashMap;
 
-enum Direction {
-    Left,
-    Right,
+/// Node names interned to dense ids, with each node's (left, right)
+/// children indexed by id. Nodes only ever named as children have none.
+struct Network {
+    names: Interner,
+    children: Vec<Option<(usize, usize)>>,
 }
 
-const AAA: u128 = 0;
-const Z: u128 = 'Z' as u128 - 'A' as u128;
-const ZZZ: u128 = (Z << 16) | (Z << 8) | Z;
+impl Network {
+    fn node(&self, name: &str) -> Option<usize> {
+        self.names.get(name)
+    }
+
+    fn name(&self, id: usize) -> &str {
+        self.names.name(id).expect("Unknown node id")
+    }
+
+    fn children(&self, id: usize) -> Option<(usize, usize)> {
+        self.children.get(id).copied().flatten()
+    }
+
+    /// Edges are labelled with the instruction that follows them. Ghosts
+    /// start on the boxes and finish on the double circles.
+    fn to_graph(&self) -> Graph {
+        let mut graph = Graph::new();
+        for (id, name) in self.names.iter() {
+            // The graph numbers nodes by itself, so its ids differ from ours.
+            let node = graph.add_node(name);
+            if let Some((left, right)) = self.children(id) {
+                graph.add_labelled_edge(name, self.name(left), Some("L".to_string()));
+                graph.add_labelled_edge(name, self.name(right), Some("R".to_string()));
+            }
+            match name.chars().last() {
+                Some('A') => graph.set_attributes(node, "shape=box"),
+                Some('Z') => graph.set_attributes(node, "shape=doublecircle"),
+                _ => (),
+            }
+        }
+        graph
+    }
+
+    fn step(&self, id: usize, direction: Direction) -> Option<usize> {
+        let (left, right) = self.children(id)?;
+        match direction {
+            Direction::Left => Some(left),
+            _ => Some(right),
+        }
+    }
+}
 
 #[derive(Debug)]
 struct ParseMapError;
-fn parse_map(input: &str) -> Result<(Vec<Direction>, HashMap<u128, (u128, u128)>), ParseMapError> {
+fn parse_map(input: &str) -> Result<(Vec<Direction>, Network), ParseMapError> {
     let mut lines = input.lines();
     let directions = lines
         .next()
         .ok_or(ParseMapError)?
+        .trim()
         .chars()
-        .filter_map(|c| match c {
-            'L' => Some(Direction::Left),
-            'R' => Some(Direction::Right),
-            _ => None,
+        .map(|c| match c {
+            'L' => Ok(Direction::Left),
+            'R' => Ok(Direction::Right),
+            _ => Err(ParseMapError),
         })
-        .collect::<Vec<Direction>>();
+        .collect::<Result<Vec<Direction>, _>>()?;
 
     lines.next();
-    let m = lines
-        .try_fold(HashMap::new(), |mut acc, line| {
-            let (source_node, children) = line.split_at(4);
-            let key = source_node
-                .trim()
-                .chars()
-                .fold(0u128, |acc, c| (acc << 8) | (c as u128 - 'A' as u128));
-            let children = children.strip_prefix("= ")?;
-            let left = children
-                .get(1..=3)?
-                .chars()
-                .fold(0u128, |acc, c| (acc << 8) | (c as u128 - 'A' as u128));
-            let right = children
-                .get(6..=8)?
-                .chars()
-                .fold(0u128, |acc, c| (acc << 8) | (c as u128 - 'A' as u128));
-
-            acc.insert(key, (left, right));
-
-            Some(acc)
-        })
-        .ok_or(ParseMapError)?;
+    let mut names = Interner::new();
+    let mut children = vec![];
+    for line in lines {
+        let (source_node, targets) = line.split_once('=').ok_or(ParseMapError)?;
+        let (left, right) = targets
+            .trim()
+            .trim_start_matches('(')
+            .trim_end_matches(')')
+            .split_once(',')
+            .ok_or(ParseMapError)?;
+
+        let ids = [source_node, left, right].map(|name| names.intern(name.trim()));
+        children.resize(names.len(), None);
+        children[ids[0]] = Some((ids[1], ids[2]));
+    }
 
-    Ok((directions, m))
+    Ok((directions, Network { names, children }))
 }
 
 #[derive(Debug)]
 struct SearchError;
 fn steps_to_reach_node(
-    start_node: u128,
-    directions: &Vec<Direction>,
-    map: &HashMap<u128, (u128, u128)>,
-    cmp: fn(u128) -> bool,
+    start_node: usize,
+    directions: &[Direction],
+    network: &Network,
+    is_end: impl Fn(usize) -> bool,
 ) -> Result<u128, SearchError> {
-    let mut current = map.get(&start_node).ok_or(SearchError)?;
+    let mut current = start_node;
     let res = directions
         .iter()
         .cycle()
         .enumerate()
-        .find_map(|(index, direction)| {
-            let next = match direction {
-                Direction::Left => current.0,
-                _ => current.1,
-            };
-
-            if cmp(next) {
-                Some(index)
-            } else {
-                current = map.get(&next)?;
-                None
-            }
-        })
-        .ok_or(SearchError)?;
+        .find_map(
+            |(index, direction)| match network.step(current, *direction) {
+                Some(next) if is_end(next) => Some(Ok(index)),
+                Some(next) => {
+                    current = next;
+                    None
+                }
+                None => Some(Err(SearchError)),
+            },
+        )
+        .ok_or(SearchError)??;
 
     Ok(res as u128 + 1)
 }
 
 #[derive(Debug)]
 struct PartOneError;
-fn run_part_one(
-    directions: Vec<Direction>,
-    map: HashMap<u128, (u128, u128)>,
-) -> Result<u128, PartOneError> {
-    let cmp = |v: u128| v == ZZZ;
-    let res = steps_to_reach_node(AAA, &directions, &map, cmp).map_err(|_| PartOneError)?;
+fn run_part_one(directions: Vec<Direction>, network: Network) -> Result<u128, PartOneError> {
+    let start = network.node("AAA").ok_or(PartOneError)?;
+    let end = network.node("ZZZ").ok_or(PartOneError)?;
+    let res = steps_to_reach_node(start, &directions, &network, |v| v == end)
+        .map_err(|_| PartOneError)?;
 
     Ok(res)
 }
@@ -105,57 +139,124 @@ impl Puzzle {
     }
 }
 
-fn gcd(a: u128, nums: &[u128]) -> u128 {
-    if let Some(&b) = nums.first() {
-        let c = (1..=a.min(b))
-            .rev()
-            .find(|v| {
-                a % v == 0 && b % v == 0
-            })
-            .unwrap_or(1);
-        gcd(c, nums.split_at(1).1)
-    } else {
-        a
+/// Where a ghost stands on an end node: at the listed steps before its walk
+/// starts repeating at `offset`, then at `cycle_hits` plus any multiple of
+/// `period`.
+#[derive(Debug, PartialEq)]
+struct GhostCycle {
+    offset: u128,
+    period: u128,
+    early_hits: Vec<u128>,
+    cycle_hits: Vec<u128>,
+}
+
+impl GhostCycle {
+    /// Walks until a (node, instruction) state repeats, which takes at most
+    /// `network.children.len() * directions.len()` steps.
+    fn find(
+        start_node: usize,
+        directions: &[Direction],
+        network: &Network,
+        is_end: impl Fn(usize) -> bool,
+    ) -> Result<Self, SearchError> {
+        let mut seen = HashMap::new();
+        let mut hits = vec![];
+        let mut node = start_node;
+        for step in 0u128.. {
+            let instruction = (step % directions.len() as u128) as usize;
+            if let Some(&offset) = seen.get(&(node, instruction)) {
+                let (early_hits, cycle_hits) = hits.iter().partition(|&&hit| hit < offset);
+                return Ok(Self {
+                    offset,
+                    period: step - offset,
+                    early_hits,
+                    cycle_hits,
+                });
+            }
+
+            seen.insert((node, instruction), step);
+            if is_end(node) {
+                hits.push(step);
+            }
+            node = network
+                .step(node, directions[instruction])
+                .ok_or(SearchError)?;
+        }
+
+        unreachable!()
+    }
+
+    fn is_hit(&self, step: u128) -> bool {
+        match step < self.offset {
+            true => self.early_hits.contains(&step),
+            false => self
+                .cycle_hits
+                .contains(&(self.offset + (step - self.offset) % self.period)),
+        }
     }
 }
 
-fn lcm(a: u128, nums: &[u128]) -> u128 {
-    if let Some(&b) = nums.first() {
-        let foo = gcd(a, &[b]);
-        let c = a * (b / foo);
-        lcm(c, nums.split_at(1).1)
-    } else {
-        a
+/// First step on which every ghost stands on an end node at once.
+fn steps_until_all_end(ghosts: &[GhostCycle]) -> Option<u128> {
+    // Before every ghost has entered its cycle, some ghost is still on its
+    // finite list of early hits, so checking that list is enough.
+    let latest = ghosts.iter().max_by_key(|g| g.offset)?;
+    if let Some(step) = latest
+        .early_hits
+        .iter()
+        .find(|&&step| step > 0 && ghosts.iter().all(|g| g.is_hit(step)))
+    {
+        return Some(*step);
     }
+
+    // Afterwards each ghost is periodic, so pick one hit per ghost and solve
+    // the system of congruences for each combination.
+    let combinations = ghosts.iter().fold(vec![vec![]], |acc, ghost| {
+        acc.iter()
+            .flat_map(|prefix: &Vec<(i128, i128)>| {
+                ghost.cycle_hits.iter().map(move |&hit| {
+                    let mut next = prefix.clone();
+                    next.push((hit as i128, ghost.period as i128));
+                    next
+                })
+            })
+            .collect::<Vec<_>>()
+    });
+    let start = latest.offset.max(1) as i128;
+    combinations
+        .iter()
+        .filter_map(|congruences| crt(congruences))
+        .map(|(residue, modulus)| start + (residue - start).rem_euclid(modulus))
+        .min()
+        .map(|step| step as u128)
 }
 
 impl super::Puzzle for Puzzle {
     fn run_part_one(&self) -> Result<super::AOCResult, Box<dyn std::error::Error>> {
-        let (directions, map) = parse_map(&self.0).expect("Issue parsing input");
-        let res = run_part_one(directions, map).expect("Issue running part one");
+        let (directions, network) = parse_map(&self.0).expect("Issue parsing input");
+        let res = run_part_one(directions, network).map_err(|_| "No path from AAA to ZZZ")?;
         Ok(super::AOCResult::U128(res))
     }
 
     fn run_part_two(&self) -> Result<super::AOCResult, Box<dyn std::error::Error>> {
-        let (directions, map) = parse_map(&self.0).expect("Issue parsing input");
-        let starting_nodes = map
-            .keys()
-            .filter(|&k| (k & 0xFF as u128) == 0)
-            .collect::<Vec<&u128>>();
-        let counts = starting_nodes
+        let (directions, network) = parse_map(&self.0).expect("Issue parsing input");
+        let is_end = |id: usize| network.name(id).ends_with('Z');
+        let ghosts = network
+            .names
             .iter()
-            .filter_map(|&k| {
-                steps_to_reach_node(*k, &directions, &map, |v| (v & 0xFF as u128) == Z)
-                    .map_err(|_| PartOneError)
-                    .ok()
-            })
-            .collect::<Vec<u128>>();
+            .filter(|(_, name)| name.ends_with('A'))
+            .map(|(id, _)| GhostCycle::find(id, &directions, &network, is_end))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| "Ghost walked off the map")?;
 
-        let a = counts.first().unwrap();
-        let b = counts.split_at(1).1;
-        let res = lcm(*a, &b);
+        let res = steps_until_all_end(&ghosts).ok_or("Ghosts never all reach an end node")?;
         Ok(super::AOCResult::U128(res))
     }
+
+    fn graph(&self) -> Option<Result<Graph, Box<dyn std::error::Error>>> {
+        let network = parse_map(&self.0).map(|(_, network)| network.to_graph());
+        Some(network.map_err(|_| "Invalid map".into()))
+    }
 }
 
 #[cfg(test)]
@@ -174,10 +275,147 @@ ZZZ = (ZZZ, ZZZ";
         assert_eq!(run_part_one(a, b).unwrap(), 6);
     }
 
+    fn ghost(network: &Network, directions: &[Direction], name: &str) -> GhostCycle {
+        let start = network.node(name).unwrap();
+        GhostCycle::find(start, directions, network, |id| {
+            network.name(id).ends_with('Z')
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_parse_map() {
+        let (directions, network) = parse_map(SAMPLE_INPUT).unwrap();
+        assert_eq!(directions.len(), 3);
+        assert_eq!(network.names.len(), 3);
+        let bbb = network.node("BBB").unwrap();
+        assert_eq!(network.name(bbb), "BBB");
+        assert_eq!(
+            network.children(bbb),
+            Some((network.node("AAA").unwrap(), network.node("ZZZ").unwrap()))
+        );
+
+        for bad in ["LER", "L0R", "LxR"] {
+            let input = SAMPLE_INPUT.replacen("LLR", bad, 1);
+            assert!(parse_map(&input).is_err());
+        }
+    }
+
+    #[test]
+    fn test_graph() {
+        let (_, network) = parse_map(SAMPLE_INPUT).unwrap();
+        let graph = network.to_graph();
+        let (aaa, zzz) = (graph.node("AAA").unwrap(), graph.node("ZZZ").unwrap());
+        assert!(graph.is_reachable(aaa, zzz));
+        assert!(!graph.is_reachable(zzz, aaa));
+        assert_eq!(graph.strongly_connected_components().len(), 2);
+        assert_eq!(
+            graph.to_dot("network"),
+            "digraph \"network\" {
+    \"AAA\" [shape=box];
+    \"BBB\";
+    \"ZZZ\" [shape=doublecircle];
+    \"AAA\" -> \"BBB\" [label=\"L\"];
+    \"AAA\" -> \"BBB\" [label=\"R\"];
+    \"BBB\" -> \"AAA\" [label=\"L\"];
+    \"BBB\" -> \"ZZZ\" [label=\"R\"];
+    \"ZZZ\" -> \"ZZZ\" [label=\"L\"];
+    \"ZZZ\" -> \"ZZZ\" [label=\"R\"];
+}
+"
+        );
+    }
+
+    #[test]
+    fn test_graph_attributes() {
+        // Children are named before their own lines, so the graph meets the
+        // nodes in a different order than the network does.
+        let (_, network) = parse_map(
+            "LR
+
+AAA = (BBB, CCC)
+DDA = (EEE, FFF)
+BBB = (GGG, HHZ)
+CCC = (CCC, CCC)
+EEE = (EEE, EEE)
+FFF = (FFF, FFF)
+GGG = (GGG, GGG)
+HHZ = (HHZ, HHZ)",
+        )
+        .unwrap();
+        let dot = network.to_graph().to_dot("network");
+        let styled = dot
+            .lines()
+            .filter(|line| line.contains('['))
+            .filter(|line| !line.contains("->"))
+            .map(str::trim)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            styled,
+            vec![
+                "\"AAA\" [shape=box];",
+                "\"HHZ\" [shape=doublecircle];",
+                "\"DDA\" [shape=box];"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_part_two() {
+        let (directions, network) = parse_map(
+            "LR
+
+11A = (11B, XXX)
+11B = (XXX, 11Z)
+11Z = (11B, XXX)
+22A = (22B, XXX)
+22B = (22C, 22C)
+22C = (22Z, 22Z)
+22Z = (22B, 22B)
+XXX = (XXX, XXX)",
+        )
+        .unwrap();
+        let ghosts = ["11A", "22A"].map(|name| ghost(&network, &directions, name));
+
+        assert_eq!(
+            ghosts[0],
+            GhostCycle {
+                offset: 1,
+                period: 2,
+                early_hits: vec![],
+                cycle_hits: vec![2]
+            }
+        );
+        assert_eq!(steps_until_all_end(&ghosts), Some(6));
+    }
+
     #[test]
-    fn test_lcm() {
-        assert_eq!(lcm(2, &vec![3]), 6);
-        assert_eq!(lcm(21, &vec![6]), 42);
-        assert_eq!(lcm(1, &vec![3, 8, 10]), 120);
+    fn test_unaligned_cycles() {
+        // AAA hits an end node on steps 2, 4, 6, ..., BBA on 1, 4, 7, ...
+        // and CCA only on step 1 before getting stuck.
+        let (directions, network) = parse_map(
+            "L
+
+AAA = (AAB, AAB)
+AAB = (AAZ, AAZ)
+AAZ = (AAC, AAC)
+AAC = (AAZ, AAZ)
+BBA = (BBZ, BBZ)
+BBZ = (BBB, BBB)
+BBB = (BBC, BBC)
+BBC = (BBZ, BBZ)
+CCA = (CCZ, CCZ)
+CCZ = (CCB, CCB)
+CCB = (CCB, CCB)",
+        )
+        .unwrap();
+        let ghost = |name: &str| ghost(&network, &directions, name);
+        let (a, b, c) = (ghost("AAA"), ghost("BBA"), ghost("CCA"));
+
+        assert_eq!((b.offset, b.period, &b.cycle_hits), (1, 3, &vec![1]));
+        assert_eq!(c.early_hits, vec![1]);
+        assert_eq!(steps_until_all_end(&[a, b]), Some(4));
+        assert_eq!(steps_until_all_end(&[ghost("BBA"), ghost("CCA")]), Some(1));
+        assert_eq!(steps_until_all_end(&[ghost("AAA"), ghost("CCA")]), None);
     }
 }
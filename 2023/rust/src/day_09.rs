@@ -1,4 +1,6 @@
-pub struct Puzzle(Vec<Vec<i32>>);
+use crate::number_theory::Rational;
+
+pub struct Puzzle(Vec<Vec<i64>>);
 
 impl Puzzle {
     fn new(input: &str) -> Self {
@@ -6,10 +8,10 @@ impl Puzzle {
             .lines()
             .map(|line| {
                 line.split_whitespace()
-                    .filter_map(|num| num.parse::<i32>().ok())
-                    .collect::<Vec<i32>>()
+                    .filter_map(|num| num.parse::<i64>().ok())
+                    .collect::<Vec<i64>>()
             })
-            .collect::<Vec<Vec<i32>>>();
+            .collect::<Vec<Vec<i64>>>();
 
         Self(sequences)
     }
@@ -17,43 +19,108 @@ impl Puzzle {
     pub fn create(input: String) -> Box<dyn super::Puzzle> {
         Box::new(Self::new(&input))
     }
+
+    fn sum_of_values_at(&self, index: impl Fn(&[i64]) -> i128) -> Option<i128> {
+        self.0.iter().try_fold(0i128, |acc, seq| {
+            let value = Polynomial::fit(seq)?.value_at(index(seq))?;
+            acc.checked_add(value)
+        })
+    }
 }
 
 impl super::Puzzle for Puzzle {
     fn run_part_one(&self) -> Result<super::AOCResult, Box<dyn std::error::Error>> {
-        let res = self
-            .0
-            .iter()
-            .map(|seq| find_next_value(&seq))
-            .reduce(|acc, v| acc + v);
-        res.map(|v| super::AOCResult::I32(v)).ok_or("Error".into())
+        let res = self.sum_of_values_at(|seq| seq.len() as i128);
+        res.map(super::AOCResult::I128).ok_or("Overflow".into())
     }
 
     fn run_part_two(&self) -> Result<super::AOCResult, Box<dyn std::error::Error>> {
-        let res = self
-            .0
-            .iter()
-            .map(|seq| {
-                let mut v = seq.clone();
-                v.reverse();
-                find_next_value(&v)
-            })
-            .reduce(|acc, v| acc + v);
-        res.map(|v| super::AOCResult::I32(v)).ok_or("Error".into())
+        let res = self.sum_of_values_at(|_| -1);
+        res.map(super::AOCResult::I128).ok_or("Overflow".into())
     }
 }
 
-fn find_next_value(sequence: &Vec<i32>) -> i32 {
-    let d = sequence
-        .windows(2)
-        .map(|w| &w[1] - &w[0])
-        .collect::<Vec<i32>>();
+/// The lowest-degree polynomial through a sequence, in Newton's forward
+/// difference form: `f(n) = sum of leading_differences[k] * C(n, k)`.
+#[derive(Debug, PartialEq)]
+pub struct Polynomial {
+    leading_differences: Vec<i128>,
+}
+
+impl Polynomial {
+    /// Takes differences until they all vanish. If the sequence runs out
+    /// first, the fit interpolates every value and its degree is one less
+    /// than the length. `None` if a difference overflows `i128`.
+    pub fn fit(sequence: &[i64]) -> Option<Self> {
+        let mut leading_differences = vec![];
+        let mut row = sequence.iter().map(|v| *v as i128).collect::<Vec<_>>();
+        while row.iter().any(|v| *v != 0) {
+            leading_differences.push(row[0]);
+            row = row
+                .windows(2)
+                .map(|w| w[1].checked_sub(w[0]))
+                .collect::<Option<_>>()?;
+        }
+
+        if leading_differences.is_empty() {
+            leading_differences.push(0);
+        }
+        Some(Self {
+            leading_differences,
+        })
+    }
+
+    pub fn degree(&self) -> usize {
+        self.leading_differences.len() - 1
+    }
+
+    /// Value at any integer index, with the first term at index 0. `None`
+    /// if it overflows `i128`.
+    pub fn value_at(&self, index: i128) -> Option<i128> {
+        // C(n, k) = C(n, k - 1) * (n - k + 1) / k divides exactly for any
+        // integer n, negative ones included.
+        let mut binomial = 1i128;
+        let mut value = 0i128;
+        for (k, difference) in self.leading_differences.iter().enumerate() {
+            if k > 0 {
+                binomial = binomial.checked_mul(index.checked_sub(k as i128 - 1)?)? / k as i128;
+            }
+            value = value.checked_add(difference.checked_mul(binomial)?)?;
+        }
+
+        Some(value)
+    }
+
+    /// Coefficients in the power basis, constant term first. `None` if an
+    /// intermediate value overflows, as `k!` does past degree 33.
+    pub fn coefficients(&self) -> Option<Vec<Rational>> {
+        let mut coefficients = vec![Rational::ZERO; self.degree() + 1];
+
+        // Integer coefficients of n * (n - 1) * ... * (n - k + 1), expanded
+        // one factor at a time, and k!.
+        let mut falling = vec![1i128];
+        let mut factorial = 1i128;
+        for (k, difference) in self.leading_differences.iter().enumerate() {
+            if k > 0 {
+                let root = k as i128 - 1;
+                let mut next = vec![0i128; falling.len() + 1];
+                for (power, c) in falling.iter().enumerate() {
+                    next[power + 1] = next[power + 1].checked_add(*c)?;
+                    next[power] = next[power].checked_sub(c.checked_mul(root)?)?;
+                }
+                falling = next;
+                factorial = factorial.checked_mul(k as i128)?;
+            }
 
-    sequence.last().unwrap_or(&0)
-        + match d.first().and_then(|f| d.iter().all(|v| v == f).then(|| f)) {
-            Some(res) => *res,
-            None => find_next_value(&d),
+            let scale = Rational::new(*difference, factorial);
+            for (power, c) in falling.iter().enumerate() {
+                let term = scale.checked_mul(Rational::from(*c))?;
+                coefficients[power] = coefficients[power].checked_add(term)?;
+            }
         }
+
+        Some(coefficients)
+    }
 }
 
 #[cfg(test)]
@@ -66,7 +133,7 @@ mod tests {
 
     #[test]
     fn test_parse_input() {
-        let p = Puzzle::new(&SAMPLE_INPUT);
+        let p = Puzzle::new(SAMPLE_INPUT);
         assert_eq!(p.0.len(), 3);
         assert_eq!(p.0[0], vec![0, 3, 6, 9, 12, 15]);
         assert_eq!(p.0[1], vec![1, 3, 6, 10, 15, 21]);
@@ -75,21 +142,97 @@ mod tests {
 
     #[test]
     fn test_find_next_value() {
-        assert_eq!(find_next_value(&vec![0, 3, 6, 9, 12, 15]), 18);
-        assert_eq!(find_next_value(&vec![1, 3, 6, 10, 15, 21]), 28);
-        assert_eq!(find_next_value(&vec![10, 13, 16, 21, 30, 45]), 68);
+        let p = Puzzle::new(SAMPLE_INPUT);
+        let next =
+            p.0.iter()
+                .map(|seq| Polynomial::fit(seq)?.value_at(6))
+                .collect::<Vec<_>>();
+        assert_eq!(next, vec![Some(18), Some(28), Some(68)]);
+        assert_eq!(p.sum_of_values_at(|seq| seq.len() as i128), Some(114));
     }
 
     #[test]
     fn test_find_prev_value() {
-        let mut v = vec![0, 3, 6, 9, 12, 15];
-        v.reverse();
-        assert_eq!(find_next_value(&v), -3);
-        let mut v = vec![1, 3, 6, 10, 15, 21];
-        v.reverse();
-        assert_eq!(find_next_value(&v), 0);
-        let mut v = vec![10, 13, 16, 21, 30, 45];
-        v.reverse();
-        assert_eq!(find_next_value(&v), 5);
+        let p = Puzzle::new(SAMPLE_INPUT);
+        let prev =
+            p.0.iter()
+                .map(|seq| Polynomial::fit(seq)?.value_at(-1))
+                .collect::<Vec<_>>();
+        assert_eq!(prev, vec![Some(-3), Some(0), Some(5)]);
+        assert_eq!(p.sum_of_values_at(|_| -1), Some(2));
+    }
+
+    #[test]
+    fn test_degree_and_coefficients() {
+        let r = |n, d| Rational::new(n, d);
+
+        let triangular = Polynomial::fit(&[1, 3, 6, 10, 15, 21]).unwrap();
+        assert_eq!(triangular.degree(), 2);
+        assert_eq!(
+            triangular.coefficients(),
+            Some(vec![r(1, 1), r(3, 2), r(1, 2)])
+        );
+
+        let linear = Polynomial::fit(&[0, 3, 6, 9]).unwrap();
+        assert_eq!(linear.degree(), 1);
+        assert_eq!(linear.coefficients(), Some(vec![r(0, 1), r(3, 1)]));
+
+        let zero = Polynomial::fit(&[0, 0, 0]).unwrap();
+        assert_eq!(zero.degree(), 0);
+        assert_eq!(zero.coefficients(), Some(vec![Rational::ZERO]));
+        assert_eq!(zero.value_at(-100), Some(0));
+
+        // Too short to vanish, so every value is interpolated.
+        let short = Polynomial::fit(&[2, 7, 1]).unwrap();
+        assert_eq!(short.degree(), 2);
+        assert_eq!(short.value_at(3), Some(-16));
+    }
+
+    #[test]
+    fn test_long_sequence() {
+        // 1000 * n^3 - 7 * n, which leaves i32 after a few dozen terms.
+        let f = |n: i128| 1000 * n.pow(3) - 7 * n;
+        let sequence = (0..200).map(|n| f(n) as i64).collect::<Vec<_>>();
+        let polynomial = Polynomial::fit(&sequence).unwrap();
+
+        assert_eq!(polynomial.degree(), 3);
+        assert_eq!(
+            polynomial.coefficients(),
+            Some(vec![
+                Rational::ZERO,
+                Rational::from(-7),
+                Rational::ZERO,
+                Rational::from(1000)
+            ])
+        );
+        assert_eq!(polynomial.value_at(200), Some(f(200)));
+        assert_eq!(polynomial.value_at(-1_000_000), Some(f(-1_000_000)));
+        assert_eq!(polynomial.value_at(10_000_000_000), Some(f(10_000_000_000)));
+        assert_eq!(polynomial.value_at(i128::MAX / 2), None);
+        assert_eq!(polynomial.value_at(i128::MIN), None);
+        assert_eq!(
+            Polynomial::fit(&[5, 5]).unwrap().value_at(i128::MIN),
+            Some(5)
+        );
+    }
+
+    #[test]
+    fn test_non_polynomial_sequence() {
+        // Never settles, so the fit interpolates all 40 values, but the
+        // power basis needs 39! in a denominator.
+        let sequence = (0..40).map(|n| n * 7919 % 13).collect::<Vec<i64>>();
+        let polynomial = Polynomial::fit(&sequence).unwrap();
+        assert_eq!(polynomial.degree(), 39);
+        assert!((0..40).all(|n| polynomial.value_at(n) == Some(sequence[n as usize] as i128)));
+        assert_eq!(polynomial.coefficients(), None);
+
+        // Differences of alternating extremes double every row, leaving
+        // i128 long before the last one.
+        let extremes = (0..80)
+            .map(|n| if n % 2 == 0 { i64::MAX } else { i64::MIN })
+            .collect::<Vec<_>>();
+        assert_eq!(Polynomial::fit(&extremes), None);
+        let p = Puzzle(vec![vec![1, 2, 3], extremes]);
+        assert_eq!(p.sum_of_values_at(|seq| seq.len() as i128), None);
     }
 }
@@ -1,3 +1,8 @@
+use crate::grid::{Direction, FromChar, Grid, Point, SignedPoint};
+use crate::image::{Image, Rgb};
+use crate::polygon::Polygon;
+use std::collections::HashSet;
+
 #[derive(Clone, Copy, Debug, PartialEq)]
 enum Pipe {
     NorthSouth,
@@ -8,54 +13,52 @@ enum Pipe {
     SouthEast,
 }
 
-#[derive(Clone, Copy, Debug)]
-enum Direction {
-    North,
-    South,
-    East,
-    West,
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Tile {
+    Pipe(Pipe),
+    Ground,
+    Start,
 }
 
-type Map = Vec<Vec<Option<Pipe>>>;
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
-struct Position {
-    x: usize,
-    y: usize,
+impl FromChar for Tile {
+    fn from_char(c: char) -> Option<Self> {
+        match c {
+            '|' => Some(Tile::Pipe(Pipe::NorthSouth)),
+            '-' => Some(Tile::Pipe(Pipe::EastWest)),
+            'L' => Some(Tile::Pipe(Pipe::NorthEast)),
+            'J' => Some(Tile::Pipe(Pipe::NorthWest)),
+            '7' => Some(Tile::Pipe(Pipe::SouthWest)),
+            'F' => Some(Tile::Pipe(Pipe::SouthEast)),
+            '.' => Some(Tile::Ground),
+            'S' => Some(Tile::Start),
+            _ => None,
+        }
+    }
 }
 
-fn get_initial_state(map: &Map, starting_position: &Position) -> Pipe {
-    let north = starting_position
-        .y
-        .checked_sub(1)
-        .and_then(|y| map.get(y).and_then(|row| row.get(starting_position.x)))
-        .unwrap_or(&None);
-    let north = match north {
+type Map = Grid<Tile>;
+
+fn get_initial_state(map: &Map, starting_position: Point) -> Pipe {
+    let neighbor = |direction: Direction| match starting_position
+        .move_direction(direction)
+        .and_then(|point| map.get(point))
+    {
+        Some(Tile::Pipe(pipe)) => Some(*pipe),
+        _ => None,
+    };
+    let north = match neighbor(Direction::Up) {
         Some(Pipe::SouthEast) | Some(Pipe::SouthWest) | Some(Pipe::NorthSouth) => true,
         _ => false,
     };
-    let south = map
-        .get(starting_position.y + 1)
-        .and_then(|row| row.get(starting_position.x))
-        .unwrap_or(&None);
-    let south = match south {
+    let south = match neighbor(Direction::Down) {
         Some(Pipe::NorthSouth) | Some(Pipe::NorthEast) | Some(Pipe::NorthWest) => true,
         _ => false,
     };
-    let east = map
-        .get(starting_position.y)
-        .and_then(|row| row.get(starting_position.x + 1))
-        .unwrap_or(&None);
-    let east = match east {
+    let east = match neighbor(Direction::Right) {
         Some(Pipe::NorthWest) | Some(Pipe::SouthWest) | Some(Pipe::EastWest) => true,
         _ => false,
     };
-    let west = starting_position
-        .x
-        .checked_sub(1)
-        .and_then(|x| map.get(starting_position.y).and_then(|row| row.get(x)))
-        .unwrap_or(&None);
-    let west = match west {
+    let west = match neighbor(Direction::Left) {
         Some(Pipe::SouthEast) | Some(Pipe::NorthEast) | Some(Pipe::EastWest) => true,
         _ => false,
     };
@@ -73,77 +76,54 @@ fn get_initial_state(map: &Map, starting_position: &Position) -> Pipe {
 
 struct Diagram {
     map: Map,
-    starting_position: Position,
+    starting_position: Point,
+    starting_pipe: Pipe,
 }
 
 impl Diagram {
-    fn new(mut map: Map, starting_position: Position) -> Self {
-        let starting_pipe_type = get_initial_state(&map, &starting_position);
-        let s = map
-            .get_mut(starting_position.y)
-            .and_then(|row| row.get_mut(starting_position.x))
-            .unwrap();
-        *s = Some(starting_pipe_type);
+    fn new(map: Map, starting_position: Point) -> Self {
+        let starting_pipe = get_initial_state(&map, starting_position);
 
         Self {
             map,
             starting_position,
+            starting_pipe,
+        }
+    }
+
+    fn pipe(&self, point: Point) -> Option<Pipe> {
+        match self.map.get(point)? {
+            Tile::Pipe(pipe) => Some(*pipe),
+            Tile::Start => Some(self.starting_pipe),
+            Tile::Ground => None,
         }
     }
 }
 
-fn parse_diagram(input: &str) -> Diagram {
-    let mut starting_position = None;
-    let m = input
-        .lines()
-        .enumerate()
-        .map(|(y, line)| {
-            line.chars()
-                .enumerate()
-                .map(|(x, c)| match c {
-                    '|' => Some(Pipe::NorthSouth),
-                    '-' => Some(Pipe::EastWest),
-                    'L' => Some(Pipe::NorthEast),
-                    'J' => Some(Pipe::NorthWest),
-                    '7' => Some(Pipe::SouthWest),
-                    'F' => Some(Pipe::SouthEast),
-                    'S' => {
-                        starting_position = Some(Position { x, y });
-                        None
-                    }
-                    _ => None,
-                })
-                .collect::<Vec<Option<Pipe>>>()
-        })
-        .collect::<Map>();
-
-    Diagram::new(
-        m,
-        starting_position.expect("Could not find starting position"),
-    )
+fn parse_diagram(input: &str) -> Result<Diagram, Box<dyn std::error::Error>> {
+    let map = input.parse::<Map>()?;
+    let starting_position = map
+        .find(|tile| *tile == Tile::Start)
+        .ok_or("Could not find starting position")?;
+
+    Ok(Diagram::new(map, starting_position))
 }
 
 struct PipeNavigator<'a> {
     diagram: &'a Diagram,
     heading: Direction,
-    position: Position,
+    position: Point,
     current_pipe: Pipe,
-    visited: Vec<Position>,
+    visited: Vec<Point>,
 }
 
 impl<'a> PipeNavigator<'a> {
     fn new(diagram: &'a Diagram) -> Self {
-        let starting_pipe = diagram
-            .map
-            .get(diagram.starting_position.y)
-            .unwrap()
-            .get(diagram.starting_position.x)
-            .unwrap()
-            .unwrap();
+        let starting_pipe = diagram.starting_pipe;
         let heading = match starting_pipe {
-            Pipe::NorthSouth | Pipe::NorthEast | Pipe::NorthWest => Direction::South,
-            Pipe::SouthWest | Pipe::SouthEast => Direction::North,
-            Pipe::EastWest => Direction::East,
+            Pipe::NorthSouth | Pipe::NorthEast | Pipe::NorthWest => Direction::Down,
+            Pipe::SouthWest | Pipe::SouthEast => Direction::Up,
+            Pipe::EastWest => Direction::Right,
         };
         let mut visited = Vec::new();
         visited.push(diagram.starting_position);
@@ -158,56 +138,31 @@ impl<'a> PipeNavigator<'a> {
     }
 
     fn step(&mut self) {
-        let (next_position, next_heading) = match (self.current_pipe, self.heading) {
-            (Pipe::NorthSouth, Direction::North)
-            | (Pipe::NorthWest, Direction::East)
-            | (Pipe::NorthEast, Direction::West) => (
-                Position {
-                    x: self.position.x,
-                    y: self.position.y - 1,
-                },
-                Direction::North,
-            ),
-            (Pipe::NorthSouth, Direction::South)
-            | (Pipe::SouthWest, Direction::East)
-            | (Pipe::SouthEast, Direction::West) => (
-                Position {
-                    x: self.position.x,
-                    y: self.position.y + 1,
-                },
-                Direction::South,
-            ),
-            (Pipe::EastWest, Direction::West)
-            | (Pipe::NorthWest, Direction::South)
-            | (Pipe::SouthWest, Direction::North) => (
-                Position {
-                    x: self.position.x - 1,
-                    y: self.position.y,
-                },
-                Direction::West,
-            ),
-            (Pipe::EastWest, Direction::East)
-            | (Pipe::NorthEast, Direction::South)
-            | (Pipe::SouthEast, Direction::North) => (
-                Position {
-                    x: self.position.x + 1,
-                    y: self.position.y,
-                },
-                Direction::East,
-            ),
+        let next_heading = match (self.current_pipe, self.heading) {
+            (Pipe::NorthSouth, Direction::Up)
+            | (Pipe::NorthWest, Direction::Right)
+            | (Pipe::NorthEast, Direction::Left) => Direction::Up,
+            (Pipe::NorthSouth, Direction::Down)
+            | (Pipe::SouthWest, Direction::Right)
+            | (Pipe::SouthEast, Direction::Left) => Direction::Down,
+            (Pipe::EastWest, Direction::Left)
+            | (Pipe::NorthWest, Direction::Down)
+            | (Pipe::SouthWest, Direction::Up) => Direction::Left,
+            (Pipe::EastWest, Direction::Right)
+            | (Pipe::NorthEast, Direction::Down)
+            | (Pipe::SouthEast, Direction::Up) => Direction::Right,
             _ => panic!(
                 "Unable to find next position: {:?}",
                 (&self.heading, &self.position, &self.current_pipe)
             ),
         };
+        let next_position = self
+            .position
+            .move_direction(next_heading)
+            .expect("Pipe leads off the map");
 
-        self.current_pipe = match self
-            .diagram
-            .map
-            .get(next_position.y)
-            .and_then(|row| row.get(next_position.x))
-        {
-            Some(Some(pipe)) => *pipe,
+        self.current_pipe = match self.diagram.pipe(next_position) {
+            Some(pipe) => pipe,
             _ => panic!("Could not get next pipe"),
         };
         self.position = next_position;
@@ -220,7 +175,7 @@ impl<'a> PipeNavigator<'a> {
     }
 }
 
-fn find_loop_points(diagram: &Diagram) -> Vec<Position> {
+fn find_loop_points(diagram: &Diagram) -> Vec<Point> {
     let mut pipe_navigator = PipeNavigator::new(&diagram);
     loop {
         pipe_navigator.step();
@@ -232,6 +187,10 @@ fn find_loop_points(diagram: &Diagram) -> Vec<Position> {
     pipe_navigator.visited
 }
 
+fn loop_polygon(loop_points: &[Point]) -> Polygon {
+    Polygon::new(loop_points.iter().map(|p| SignedPoint::from(*p)).collect())
+}
+
 pub struct Puzzle(String);
 
 impl Puzzle {
@@ -246,7 +205,7 @@ impl Puzzle {
 
 impl super::Puzzle for Puzzle {
     fn run_part_one(&self) -> Result<super::AOCResult, Box<dyn std::error::Error>> {
-        let diagram = parse_diagram(&self.0);
+        let diagram = parse_diagram(&self.0)?;
         let loop_points = find_loop_points(&diagram);
 
         let length = loop_points.len();
@@ -255,21 +214,35 @@ impl super::Puzzle for Puzzle {
     }
 
     fn run_part_two(&self) -> Result<super::AOCResult, Box<dyn std::error::Error>> {
-        let diagram = parse_diagram(&self.0);
+        let diagram = parse_diagram(&self.0)?;
         let loop_points = find_loop_points(&diagram);
 
-        let sum = loop_points.windows(2).fold(0i32, |acc, w| {
-            let x1 = w[0].x as i32;
-            let x2 = w[1].x as i32;
-            let y1 = w[0].y as i32;
-            let y2 = w[1].y as i32;
-            acc + ((x2 + x1) * (y2 - y1))
-        });
-        let area = sum.abs() as usize >> 1;
-        let boundary_points = loop_points.len();
-        let res = area - (boundary_points >> 1) + 1;
-
-        Ok(super::AOCResult::USize(res))
+        let res = loop_polygon(&loop_points)
+            .interior_points()
+            .ok_or("No loop found")?;
+
+        Ok(super::AOCResult::USize(res as usize))
+    }
+
+    fn image(&self, scale: usize) -> Option<Result<Image, Box<dyn std::error::Error>>> {
+        let diagram = match parse_diagram(&self.0) {
+            Ok(diagram) => diagram,
+            Err(e) => return Some(Err(e)),
+        };
+        let loop_points = find_loop_points(&diagram)
+            .into_iter()
+            .collect::<HashSet<_>>();
+
+        Some(Ok(Image::from_grid(
+            &diagram.map,
+            scale,
+            |point, tile| match tile {
+                Tile::Start => Rgb(220, 40, 40),
+                _ if loop_points.contains(&point) => Rgb(250, 200, 40),
+                Tile::Pipe(_) => Rgb(70, 70, 70),
+                Tile::Ground => Rgb::BLACK,
+            },
+        )))
     }
 }
 
@@ -285,9 +258,10 @@ L|-JF";
 
     #[test]
     fn test_parse_sample_input() {
-        let map = parse_diagram(&SAMPLE_INPUT);
-        assert_eq!(map.map.len(), 5);
-        assert_eq!(map.map[0].len(), 5);
+        let map = parse_diagram(&SAMPLE_INPUT).unwrap();
+        assert_eq!(map.map.height(), 5);
+        assert_eq!(map.map.width(), 5);
+        assert_eq!(map.starting_position, Point(1, 1));
     }
 
     #[test]
@@ -302,23 +276,12 @@ L|-JF";
 .|..|.|..|.
 .L--J.L--J.
 ...........",
-        );
-        let loop_points = find_loop_points(&diagram);
+        )
+        .unwrap();
+        let polygon = loop_polygon(&find_loop_points(&diagram));
 
-        let sum = loop_points.windows(2).fold(0i32, |acc, w| {
-            let x1 = w[0].x as i32;
-            let x2 = w[1].x as i32;
-            let y1 = w[0].y as i32;
-            let y2 = w[1].y as i32;
-            let ysub = y2
-                .checked_sub(y1)
-                .expect(format!("{} - {}", y2, y1).as_str());
-            acc + ((x2 + x1) * (ysub))
-        });
-        let area = sum.abs() as usize >> 1;
-        let boundary_points = loop_points.len();
-        let res = area - (boundary_points >> 1) + 1;
-
-        assert_eq!(res, 4);
+        assert_eq!(polygon.area(), Some(26));
+        assert_eq!(polygon.boundary_points(), 46);
+        assert_eq!(polygon.interior_points(), Some(4));
     }
 }
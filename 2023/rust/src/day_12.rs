@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use crate::memo::Memo;
 use std::str::FromStr;
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
@@ -50,98 +50,58 @@ impl FromStr for Record {
     }
 }
 
-fn count_arrangements(
-    groups: &[Vec<Condition>],
-    blocks: &[usize],
-    cache: &mut HashMap<(Vec<Vec<Condition>>, Vec<usize>), u128>,
-) -> u128 {
-    let group_split = groups.split_first();
-    if group_split.is_none() {
-        return if blocks.len() > 0 { 0 } else { 1 };
-    }
-    let (group, remaining_groups) = group_split.unwrap();
+/// Position in a record: the first unmatched spring is
+/// `spring_groups[group][offset]` and the next block to place is
+/// `code[block]`.
+type State = (usize, usize, usize);
 
-    let block_split = blocks.split_first();
-    if block_split.is_none() {
-        return if groups.iter().any(|g| g.contains(&Condition::Broken)) {
-            0
-        } else {
-            1
-        };
-    }
-    let (&block, remaining_blocks) = block_split.unwrap();
+fn count_arrangements(record: &Record, memo: &mut Memo<State, u128>) -> u128 {
+    arrangements_from(record, (0, 0, 0), memo)
+}
 
-    if block > group.len() && group.contains(&Condition::Broken) {
-        return 0;
-    }
+fn arrangements_from(record: &Record, state: State, memo: &mut Memo<State, u128>) -> u128 {
+    let (group_index, offset, block_index) = state;
+    let Some(group) = record.spring_groups.get(group_index).map(|g| &g[offset..]) else {
+        return (block_index == record.code.len()) as u128;
+    };
 
-    match cache.get(&(groups.to_vec(), blocks.to_vec())) {
-        Some(cached) => {
-            return *cached;
-        }
-        None => (),
-    }
+    let Some(&block) = record.code.get(block_index) else {
+        let mut remaining = record.spring_groups[group_index + 1..].iter();
+        let broken_left =
+            group.contains(&Condition::Broken) || remaining.any(|g| g.contains(&Condition::Broken));
+        return (!broken_left) as u128;
+    };
 
-    let mut possible_arrangements: u128 = 0;
-    if !group.contains(&Condition::Broken) {
-        possible_arrangements = count_arrangements(remaining_groups, blocks, cache);
+    if block > group.len() && group.contains(&Condition::Broken) {
+        return 0;
     }
 
-    let mut offset = 0usize;
-    let mut next_calls: HashMap<Vec<Vec<Condition>>, usize> = HashMap::new();
-    loop {
-        if offset + block > group.len() {
-            break;
+    memo.get_or_insert_with(state, |memo| {
+        let mut possible_arrangements: u128 = 0;
+        if !group.contains(&Condition::Broken) {
+            possible_arrangements +=
+                arrangements_from(record, (group_index + 1, 0, block_index), memo);
         }
 
-        match offset.checked_sub(2).and_then(|i| group.get(i)) {
-            Some(Condition::Broken) => {
+        // Slide the block along the group until it would skip a broken spring.
+        for start in 0..(group.len() + 1).saturating_sub(block) {
+            if start > 0 && group[start - 1] == Condition::Broken {
                 break;
             }
-            _ => (),
-        };
-        match offset.checked_sub(1).and_then(|i| group.get(i)) {
-            Some(Condition::Broken) => {
-                offset += 1;
-                continue;
-            }
-            _ => (),
-        };
-        match group.get(offset + block) {
-            Some(Condition::Broken) => {
-                offset += 1;
+            if group.get(start + block) == Some(&Condition::Broken) {
                 continue;
             }
-            _ => (),
-        }
 
-        let mid = offset + block + 1;
-        let next_call = if mid < group.len() {
-            let (_, remainder) = group.split_at(offset + block + 1);
-            let mut new_groups = remaining_groups.to_owned();
-            new_groups.insert(0, remainder.to_owned());
-            new_groups
-        } else {
-            remaining_groups.to_owned()
-        };
-        match next_calls.get_mut(&next_call) {
-            Some(count) => {
-                *count += 1;
-            }
-            None => {
-                next_calls.insert(next_call, 1);
-            }
+            let end = start + block + 1;
+            let next = match end < group.len() {
+                true => (group_index, offset + end, block_index + 1),
+                false => (group_index + 1, 0, block_index + 1),
+            };
+            possible_arrangements += arrangements_from(record, next, memo);
         }
 
-        offset += 1;
-    }
-    possible_arrangements += next_calls
-        .iter()
-        .map(|(call, c)| *c as u128 * count_arrangements(call, remaining_blocks, cache))
-        .sum::<u128>();
-
-    cache.insert((groups.to_vec(), blocks.to_vec()), possible_arrangements);
-    possible_arrangements
+        possible_arrangements
+    })
 }
 
 pub struct Puzzle(String);
@@ -158,13 +118,12 @@ impl Puzzle {
 
 impl super::Puzzle for Puzzle {
     fn run_part_one(&self) -> Result<super::AOCResult, Box<dyn std::error::Error>> {
-        let mut cache = HashMap::new();
         let res = self
             .0
             .lines()
             .map(|l| {
                 let record = l.parse::<Record>().unwrap();
-                count_arrangements(&record.spring_groups, &record.code, &mut cache)
+                count_arrangements(&record, &mut Memo::new())
             })
             .sum::<u128>();
 
@@ -172,7 +131,6 @@ impl super::Puzzle for Puzzle {
     }
 
     fn run_part_two(&self) -> Result<super::AOCResult, Box<dyn std::error::Error>> {
-        let mut cache = HashMap::new();
         let res = self
             .0
             .lines()
@@ -184,7 +142,7 @@ impl super::Puzzle for Puzzle {
                 let new_code = format!("{},{},{},{},{}", b, b, b, b, b);
                 let new_line = format!("{} {}", new_cond, new_code);
                 let record = new_line.parse::<Record>().unwrap();
-                count_arrangements(&record.spring_groups, &record.code, &mut cache)
+                count_arrangements(&record, &mut Memo::new())
             })
             .sum::<u128>();
 
@@ -202,8 +160,7 @@ mod tests {
         fn $name() {
             let (record, expected) = $value;
             let record = record.parse::<Record>().unwrap();
-            let mut cache = HashMap::new();
-            assert_eq!(expected, count_arrangements(&record.spring_groups, &record.code, &mut cache));
+            assert_eq!(expected, count_arrangements(&record, &mut Memo::new()));
         }
     )*
     }
@@ -243,15 +200,29 @@ mod tests {
 
     #[test]
     fn test_sample_input() {
-        let mut cache = HashMap::new();
         let res = SAMPLE_INPUT
             .lines()
             .map(|l| {
                 let record = l.parse::<Record>().unwrap();
-                count_arrangements(&record.spring_groups, &record.code, &mut cache)
+                count_arrangements(&record, &mut Memo::new())
             })
             .sum::<u128>();
 
         assert_eq!(res, 21);
     }
+
+    #[test]
+    fn test_unfolded_record() {
+        let record = "????.######..#####.?????.######..#####.?????.######..#####.?????.######..#####.?????.######..#####. 1,6,5,1,6,5,1,6,5,1,6,5,1,6,5"
+            .parse::<Record>()
+            .unwrap();
+
+        let mut memo = Memo::new();
+        assert_eq!(count_arrangements(&record, &mut memo), 2500);
+        assert!(memo.stats().hits > 0);
+
+        let mut memo = Memo::bounded(4);
+        assert_eq!(count_arrangements(&record, &mut memo), 2500);
+        assert!(memo.len() <= 4);
+    }
 }
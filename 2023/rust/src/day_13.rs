@@ -1,35 +1,24 @@
+use crate::bitgrid::BitGrid;
+use crate::grid::Grid;
+
 #[derive(Debug, PartialEq)]
-struct Pattern(Vec<u32>, Vec<u32>);
+struct Pattern(BitGrid, BitGrid);
 
 impl Pattern {
-    fn new(horizontal: Vec<u32>, vertical: Vec<u32>) -> Self {
+    fn new(horizontal: BitGrid, vertical: BitGrid) -> Self {
         Self(horizontal, vertical)
     }
 
     fn parse(input: &str) -> Self {
-        let vertical = input.lines().fold(
-            vec![0; input.lines().next().unwrap_or("").len()],
-            |acc, line| {
-                line.chars()
-                    .zip(acc)
-                    .map(|(c, acc)| match c {
-                        '#' => acc << 1 | 1,
-                        '.' => acc << 1,
-                        _ => panic!("Invalid character"),
-                    })
-                    .collect::<Vec<u32>>()
-            },
-        );
-        let horizontal = input
-            .lines()
-            .map(|line| {
-                line.chars().fold(0u32, |acc, c| match c {
-                    '#' => acc << 1 | 1,
-                    '.' => acc << 1,
-                    _ => panic!("Invalid character"),
-                })
-            })
-            .collect::<Vec<u32>>();
+        let grid = Grid::parse_with(input, |c| match c {
+            '#' => Some(true),
+            '.' => Some(false),
+            _ => None,
+        })
+        .expect("Invalid character");
+
+        let horizontal = BitGrid::from_grid(&grid, |ash| *ash);
+        let vertical = horizontal.transpose();
         Self::new(horizontal, vertical)
     }
 
@@ -40,19 +29,16 @@ impl Pattern {
             .collect::<Vec<Self>>()
     }
 
-    fn find_reflection(&self, expected_smudge_count: u32) -> (Option<usize>, Option<usize>) {
-        let f = |v: &Vec<u32>| -> Option<usize> {
-            match v.iter().enumerate().skip(1).find(|(index, _)| {
+    fn find_reflection(&self, expected_smudge_count: usize) -> (Option<usize>, Option<usize>) {
+        let f = |v: &BitGrid| -> Option<usize> {
+            (1..v.height()).find(|index| {
                 let mut offset = 0;
                 let mut smudge_count = 0;
                 loop {
-                    let left = index
-                        .checked_sub(offset)
-                        .and_then(|i| i.checked_sub(1))
-                        .and_then(|i| v.get(i));
-                    let right = v.get(index + offset);
+                    let left = index.checked_sub(offset + 1);
+                    let right = Some(index + offset).filter(|&i| i < v.height());
                     if let (Some(left), Some(right)) = (left, right) {
-                        smudge_count += (left ^ right).count_ones();
+                        smudge_count += v.row_distance(left, right);
                         match smudge_count <= expected_smudge_count {
                             true => offset += 1,
                             _ => break,
@@ -63,10 +49,7 @@ impl Pattern {
                 }
 
                 false
-            }) {
-                Some((index, _)) => Some(index),
-                _ => None,
-            }
+            })
         };
 
         if let Some(h) = f(&self.0) {
@@ -156,23 +139,32 @@ mod tests {
 ..##..##.
 #.#.##.#.",
         );
+        assert_eq!((p.0.width(), p.0.height()), (9, 7));
+        assert_eq!((p.1.width(), p.1.height()), (7, 9));
+        assert_eq!(
+            (0..7).map(|y| p.0.row_count(y)).collect::<Vec<_>>(),
+            vec![5, 4, 3, 3, 4, 4, 5]
+        );
+        assert_eq!(p.0.row(0), &[0b011001101]);
+        assert_eq!(p.1.row(0), &[0b1001101]);
+    }
+
+    #[test]
+    fn test_wide_pattern() {
+        let row = "#.".repeat(20) + "##" + &".#".repeat(20);
+        let pattern = [row.as_str(), "#".repeat(82).as_str(), row.as_str()].join("\n");
+        let p = Pattern::parse(&pattern);
+        assert_eq!(p.find_reflection(0), (None, Some(41)));
+
+        let smudged = [
+            row.as_str(),
+            &(".".to_string() + &"#".repeat(81)),
+            row.as_str(),
+        ]
+        .join("\n");
         assert_eq!(
-            p,
-            Pattern::new(
-                vec![
-                    0b101100110,
-                    0b001011010,
-                    0b110000001,
-                    0b110000001,
-                    0b001011010,
-                    0b001100110,
-                    0b101011010
-                ],
-                vec![
-                    0b1011001, 0b0011000, 0b1100111, 0b1000010, 0b0100101, 0b0100101, 0b1000010,
-                    0b1100111, 0b0011000
-                ]
-            )
+            Pattern::parse(&smudged).find_reflection(1),
+            (None, Some(41))
         );
     }
 
@@ -1,151 +1,109 @@
+use crate::animation::Recorder;
+use crate::cycle;
+use crate::grid::{Direction, Grid, Point};
+use crate::image::{Image, Rgb};
+use crate::render::Renderer;
+use std::collections::HashSet;
 use std::fmt;
 
+const SPIN_CYCLES: usize = 1_000_000_000;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 enum Rock {
     Round,
     Cube,
 }
 
-#[derive(PartialEq)]
-enum Direction {
-    North,
-    South,
-    East,
-    West,
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct Platform {
+    rocks: Grid<Option<Rock>>,
 }
 
-pub struct Platform {
-    size: usize,
-    rocks: Vec<Option<Rock>>,
+fn rock_char(rock: &Option<Rock>) -> char {
+    match rock {
+        Some(Rock::Round) => 'O',
+        Some(Rock::Cube) => '#',
+        None => '.',
+    }
+}
+
+fn rock_color(rock: &Option<Rock>) -> Rgb {
+    match rock {
+        Some(Rock::Round) => Rgb(200, 200, 200),
+        Some(Rock::Cube) => Rgb(110, 70, 40),
+        None => Rgb::BLACK,
+    }
 }
 
 impl fmt::Display for Platform {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        self.rocks.iter().enumerate().try_for_each(|(index, rock)| {
-            if index % self.size == 0 && index != 0 {
-                writeln!(f)?;
-            }
-
-            write!(
-                f,
-                "{}",
-                match rock {
-                    Some(Rock::Round) => 'O',
-                    Some(Rock::Cube) => '#',
-                    None => '.',
-                }
-            )
-        })
+        let renderer = Renderer::new(&self.rocks, |_, rock| rock_char(rock)).colors(false);
+        write!(f, "{}", renderer)
     }
 }
 
 impl Platform {
-    fn new(rocks: Vec<Option<Rock>>, size: usize) -> Self {
-        Self { rocks, size }
-    }
-
-    fn key(&self) -> String {
-        self.rocks
-            .iter()
-            .map(|r| match r {
-                Some(Rock::Round) => 'O',
-                Some(Rock::Cube) => '#',
-                None => '.',
-            })
-            .collect::<String>()
+    fn new(rocks: Grid<Option<Rock>>) -> Self {
+        Self { rocks }
     }
 
     fn from_input(input: &str) -> Self {
-        let size = input.lines().count();
-        let rocks = input
-            .lines()
-            .map(|line| {
-                line.chars()
-                    .map(|c| match c {
-                        '.' => None,
-                        'O' => Some(Rock::Round),
-                        '#' => Some(Rock::Cube),
-                        _ => panic!("Invalid input"),
-                    })
-                    .collect::<Vec<_>>()
-            })
-            .flatten()
-            .collect::<Vec<_>>();
-
-        Self::new(rocks, size)
-    }
+        let rocks = Grid::parse_with(input, |c| match c {
+            '.' => Some(None),
+            'O' => Some(Some(Rock::Round)),
+            '#' => Some(Some(Rock::Cube)),
+            _ => None,
+        })
+        .expect("Invalid input");
 
-    fn tilt(&mut self, direction: Direction) -> () {
-        let mut v = vec![None; self.size * self.size];
-        for i in 0..self.size {
-            let mut list = match direction {
-                Direction::North | Direction::South => self
-                    .rocks
-                    .iter()
-                    .skip(i)
-                    .step_by(self.size)
-                    .collect::<Vec<_>>(),
-                Direction::West | Direction::East => self
-                    .rocks
-                    .iter()
-                    .skip(i * self.size)
-                    .take(self.size)
-                    .collect::<Vec<_>>(),
-            };
-            if direction == Direction::South || direction == Direction::East {
-                list.reverse();
-            }
+        Self::new(rocks)
+    }
 
+    fn tilt_north(&mut self) {
+        for x in 0..self.rocks.width() {
             let mut last_insert_pos = 0;
-            let mut foo = list
-                .iter()
-                .enumerate()
-                .fold(vec![None; list.len()], |mut acc, a| {
-                    match a.1 {
-                        Some(Rock::Round) => {
-                            acc[last_insert_pos] = Some(Rock::Round);
-                            last_insert_pos += 1;
-                        }
-                        Some(Rock::Cube) => {
-                            acc[a.0] = Some(Rock::Cube);
-                            last_insert_pos = a.0 + 1;
-                        }
-                        _ => (),
-                    };
-
-                    acc
-                });
-
-            if direction == Direction::South || direction == Direction::East {
-                foo.reverse();
-            }
-
-            match direction {
-                Direction::North | Direction::South => {
-                    foo.iter().enumerate().for_each(|(index, r)| {
-                        v[index * self.size + i] = r.clone();
-                    });
-                }
-                Direction::West | Direction::East => {
-                    v.splice((i * self.size)..((i + 1) * self.size), foo);
+            for y in 0..self.rocks.height() {
+                match self.rocks[Point(x, y)] {
+                    Some(Rock::Round) => {
+                        self.rocks[Point(x, y)] = None;
+                        self.rocks[Point(x, last_insert_pos)] = Some(Rock::Round);
+                        last_insert_pos += 1;
+                    }
+                    Some(Rock::Cube) => {
+                        last_insert_pos = y + 1;
+                    }
+                    None => (),
                 }
-            };
+            }
         }
+    }
+
+    fn tilt(&mut self, direction: Direction) {
+        let turns = std::iter::successors(Some(direction), |d| Some(d.turn_right()))
+            .take(4)
+            .position(|d| d == Direction::Up)
+            .expect("Platforms can only be tilted orthogonally");
 
-        self.rocks = v;
+        (0..turns).for_each(|_| self.rocks = self.rocks.rotate_clockwise());
+        self.tilt_north();
+        (0..turns).for_each(|_| self.rocks = self.rocks.rotate_counter_clockwise());
     }
 
     fn cycle(&mut self) {
-        self.tilt(Direction::North);
-        self.tilt(Direction::West);
-        self.tilt(Direction::South);
-        self.tilt(Direction::East);
+        (0..4).for_each(|_| {
+            self.tilt_north();
+            self.rocks = self.rocks.rotate_clockwise();
+        });
+    }
+
+    fn image(&self, scale: usize) -> Image {
+        Image::from_grid(&self.rocks, scale, |_, rock| rock_color(rock))
     }
 
     fn calculate_load(&self) -> usize {
-        self.rocks.iter().enumerate().fold(0, |acc, (index, rock)| {
+        self.rocks.iter_cells().fold(0, |acc, (point, rock)| {
             if rock == &Some(Rock::Round) {
-                let load = self.size - (index / self.size);
+                let load = self.rocks.height() - point.1;
                 acc + load
             } else {
                 acc
@@ -169,50 +127,45 @@ impl Puzzle {
 impl super::Puzzle for Puzzle {
     fn run_part_one(&self) -> Result<super::AOCResult, Box<dyn std::error::Error>> {
         let mut platform = Platform::from_input(&self.0);
-        platform.tilt(Direction::North);
+        platform.tilt(Direction::Up);
 
         let load = platform.calculate_load();
         Ok(super::AOCResult::USize(load))
     }
 
     fn run_part_two(&self) -> Result<super::AOCResult, Box<dyn std::error::Error>> {
-        let mut cache: std::collections::HashMap<String, (_, usize)> =
-            std::collections::HashMap::new();
+        let platform = Platform::from_input(&self.0);
+        let platform = cycle::fast_forward(platform, SPIN_CYCLES, Platform::cycle);
+
+        Ok(super::AOCResult::USize(platform.calculate_load()))
+    }
+
+    fn image(&self, scale: usize) -> Option<Result<Image, Box<dyn std::error::Error>>> {
         let mut platform = Platform::from_input(&self.0);
-        cache.insert(platform.key(), (platform.rocks.clone(), 0));
-        let mut res: Option<usize> = None;
-        loop {
-            platform.cycle();
-            let cycle_number = cache.len();
-
-            if let Some(m) = cache.get(&platform.key()) {
-                let k = cycle_number - m.1;
-                let goal = ((1_000_000_000 - m.1) % k) + m.1;
-                if let Some(v_n) = cache.iter().find_map(|(_, value)| {
-                    if value.1 == goal {
-                        Some(value.0.to_vec())
-                    } else {
-                        None
-                    }
-                }) {
-                    let goal = Platform {
-                        rocks: v_n,
-                        size: platform.size,
-                    };
-                    res = Some(goal.calculate_load());
-                }
+        platform.tilt(Direction::Up);
 
-                break;
-            } else {
-                cache.insert(platform.key(), (platform.rocks.clone(), cycle_number));
-            }
+        Some(Ok(platform.image(scale)))
+    }
 
-            if cycle_number == 1_000_000_000 {
-                break;
-            }
+    fn animate(&self, recorder: &mut Recorder) -> Option<Result<(), Box<dyn std::error::Error>>> {
+        let mut platform = Platform::from_input(&self.0);
+        let mut seen = HashSet::new();
+        recorder.record(&platform.rocks, |_, rock| rock_color(rock));
+        while seen.insert(platform.rocks.clone()) {
+            [
+                Direction::Up,
+                Direction::Left,
+                Direction::Down,
+                Direction::Right,
+            ]
+            .into_iter()
+            .for_each(|direction| {
+                platform.tilt(direction);
+                recorder.record(&platform.rocks, |_, rock| rock_color(rock));
+            });
         }
-        res.map(|v| super::AOCResult::USize(v))
-            .ok_or("Error".into())
+
+        Some(Ok(()))
     }
 }
 
@@ -234,48 +187,56 @@ O.#..O.#.#
     #[test]
     fn test_parse_input() {
         let platform = Platform::from_input(SAMPLE_INPUT);
-        assert_eq!(platform.size, 10);
-        assert_eq!(platform.rocks.len(), 100);
+        assert_eq!(platform.rocks.width(), 10);
+        assert_eq!(platform.rocks.height(), 10);
+        assert_eq!(platform.to_string(), SAMPLE_INPUT);
     }
 
     #[test]
     fn test_tilt() {
         let mut platform = Platform::from_input(SAMPLE_INPUT);
-        platform.tilt(Direction::North);
+        platform.tilt(Direction::Up);
 
         assert_eq!(platform.calculate_load(), 136);
     }
 
+    #[test]
+    fn test_tilt_directions() {
+        let mut platform = Platform::from_input("O.#\n.O.\n#.O");
+        platform.tilt(Direction::Right);
+        assert_eq!(platform.to_string(), ".O#\n..O\n#.O");
+        platform.tilt(Direction::Down);
+        assert_eq!(platform.to_string(), "..#\n..O\n#OO");
+        platform.tilt(Direction::Left);
+        assert_eq!(platform.to_string(), "..#\nO..\n#OO");
+        platform.tilt(Direction::Up);
+        assert_eq!(platform.to_string(), "OO#\n..O\n#..");
+    }
+
     #[test]
     fn test_cycle() {
-        let mut cache: std::collections::HashMap<_, usize> = std::collections::HashMap::new();
-        let mut platform = Platform::from_input(SAMPLE_INPUT);
-        cache.insert(platform.rocks.clone(), 0);
-        let mut res: Option<usize> = None;
-        loop {
-            platform.cycle();
-            let cycle_number = cache.len();
-
-            if let Some(m) = cache.get(&platform.rocks) {
-                let k = cycle_number - m;
-                let goal = ((1_000_000_000 - m) % k) + m;
-                if let Some(v_n) = cache.iter().find(|(_, value)| *value == &goal) {
-                    let goal = Platform {
-                        rocks: v_n.0.to_vec(),
-                        size: platform.size,
-                    };
-                    res = Some(goal.calculate_load());
-                }
+        let platform = Platform::from_input(SAMPLE_INPUT);
+        let cycle = cycle::brent(&platform, Platform::cycle);
+        assert_eq!((cycle.start, cycle.length), (3, 7));
 
-                break;
-            } else {
-                cache.insert(platform.rocks.clone(), cycle_number);
-            }
+        let spun = cycle::fast_forward(platform.clone(), SPIN_CYCLES, Platform::cycle);
+        assert_eq!(spun.calculate_load(), 64);
+        let spun = cycle::fast_forward_brent(platform, SPIN_CYCLES, Platform::cycle);
+        assert_eq!(spun.calculate_load(), 64);
+    }
 
-            if cycle_number == 1_000_000_000 {
-                break;
-            }
-        }
-        assert_eq!(res.unwrap(), 64);
+    #[test]
+    fn test_animate() {
+        use crate::Puzzle as _;
+
+        let mut recorder = Recorder::new(1, 50, 3);
+        Puzzle::new(SAMPLE_INPUT)
+            .animate(&mut recorder)
+            .unwrap()
+            .unwrap();
+        assert_eq!(recorder.frames().len(), 11);
+        assert_eq!(recorder.frames()[1].pixel(0, 0), Some(Rgb::BLACK));
+        assert_eq!(recorder.frames()[1].pixel(4, 9), Some(Rgb(200, 200, 200)));
+        assert!(recorder.to_gif().is_ok());
     }
 }
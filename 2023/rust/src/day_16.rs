@@ -1,3 +1,8 @@
+use crate::animation::Recorder;
+use crate::bitgrid::BitGrid;
+use crate::grid::{self, Direction, FromChar, Point, SignedPoint};
+use crate::image::{Image, Rgb};
+use crate::render::Renderer;
 use std::collections::HashSet;
 
 #[derive(Clone, Copy, PartialEq, Eq)]
@@ -9,63 +14,41 @@ enum Tile {
     HSplitter,
 }
 
-impl std::fmt::Display for Tile {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let c = match self {
+impl Tile {
+    fn glyph(&self) -> char {
+        match self {
             Tile::Empty => '.',
             Tile::FMirror => '/',
             Tile::BMirror => '\\',
             Tile::VSplitter => '|',
             Tile::HSplitter => '-',
-        };
-        write!(f, "{}", c)
+        }
     }
 }
 
-impl std::fmt::Debug for Tile {
+impl std::fmt::Display for Tile {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self)
+        write!(f, "{}", self.glyph())
     }
 }
 
-#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
-struct Position {
-    x: usize,
-    y: usize,
-}
-
-struct IndexOutOfBoundsError;
-impl Position {
-    fn move_direction(&self, dir: Direction) -> Result<Self, IndexOutOfBoundsError> {
-        let pos = match dir {
-            Direction::Up => Position {
-                x: self.x,
-                y: self.y.checked_sub(1).ok_or(IndexOutOfBoundsError)?,
-            },
-            Direction::Down => Position {
-                x: self.x,
-                y: self.y + 1,
-            },
-            Direction::Left => Position {
-                x: self.x.checked_sub(1).ok_or(IndexOutOfBoundsError)?,
-                y: self.y,
-            },
-            Direction::Right => Position {
-                x: self.x + 1,
-                y: self.y,
-            },
-        };
-
-        Ok(pos)
+impl FromChar for Tile {
+    fn from_char(c: char) -> Option<Self> {
+        match c {
+            '.' => Some(Tile::Empty),
+            '/' => Some(Tile::FMirror),
+            '\\' => Some(Tile::BMirror),
+            '|' => Some(Tile::VSplitter),
+            '-' => Some(Tile::HSplitter),
+            _ => None,
+        }
     }
 }
 
-#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
-enum Direction {
-    Up,
-    Down,
-    Left,
-    Right,
+impl std::fmt::Debug for Tile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self)
+    }
 }
 
 enum CollisionResult {
@@ -78,49 +61,40 @@ impl Tile {
     fn collision(&self, dir: Direction) -> CollisionResult {
         match self {
             Tile::Empty => CollisionResult::Continue,
-            Tile::FMirror => match dir {
-                Direction::Up => CollisionResult::Turn(Direction::Right),
-                Direction::Down => CollisionResult::Turn(Direction::Left),
-                Direction::Left => CollisionResult::Turn(Direction::Down),
-                Direction::Right => CollisionResult::Turn(Direction::Up),
-            },
-            Tile::BMirror => match dir {
-                Direction::Up => CollisionResult::Turn(Direction::Left),
-                Direction::Down => CollisionResult::Turn(Direction::Right),
-                Direction::Left => CollisionResult::Turn(Direction::Up),
-                Direction::Right => CollisionResult::Turn(Direction::Down),
-            },
-            Tile::VSplitter => match dir {
-                Direction::Left | Direction::Right => {
-                    CollisionResult::Split(Direction::Up, Direction::Down)
-                }
-                Direction::Down | Direction::Up => CollisionResult::Continue,
-            },
-            Tile::HSplitter => match dir {
-                Direction::Up | Direction::Down => {
-                    CollisionResult::Split(Direction::Left, Direction::Right)
-                }
-                Direction::Left | Direction::Right => CollisionResult::Continue,
-            },
+            Tile::FMirror if dir.is_vertical() => CollisionResult::Turn(dir.turn_right()),
+            Tile::FMirror => CollisionResult::Turn(dir.turn_left()),
+            Tile::BMirror if dir.is_vertical() => CollisionResult::Turn(dir.turn_left()),
+            Tile::BMirror => CollisionResult::Turn(dir.turn_right()),
+            Tile::VSplitter if dir.is_horizontal() => {
+                CollisionResult::Split(Direction::Up, Direction::Down)
+            }
+            Tile::HSplitter if dir.is_vertical() => {
+                CollisionResult::Split(Direction::Left, Direction::Right)
+            }
+            Tile::VSplitter | Tile::HSplitter => CollisionResult::Continue,
         }
     }
 }
 
+fn tile_color(tile: &Tile, energized: bool) -> Rgb {
+    match (energized, tile) {
+        (true, Tile::Empty) => Rgb(250, 200, 40),
+        (true, _) => Rgb(255, 240, 160),
+        (false, Tile::Empty) => Rgb::BLACK,
+        (false, _) => Rgb(90, 90, 110),
+    }
+}
+
 #[derive(Clone)]
 struct Grid {
-    tiles: Vec<Tile>,
-    size: usize,
-    beams: Vec<(Position, Direction)>,
+    tiles: grid::Grid<Tile>,
+    beams: Vec<(Point, Direction)>,
 }
 
 impl std::fmt::Display for Grid {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        self.tiles.iter().enumerate().try_for_each(|(i, c)| {
-            if i % self.size == 0 && i > 0 {
-                writeln!(f)?;
-            }
-            write!(f, "{}", c)
-        })
+        let renderer = Renderer::new(&self.tiles, |_, tile| tile.glyph()).colors(false);
+        write!(f, "{}", renderer)
     }
 }
 
@@ -131,40 +105,28 @@ impl std::fmt::Debug for Grid {
 }
 
 impl Grid {
-    fn new(tiles: Vec<Tile>, size: usize) -> Self {
+    fn new(tiles: grid::Grid<Tile>) -> Self {
         Self {
             tiles,
-            size,
             beams: vec![],
         }
     }
 
-    fn from_input(input: &str) -> Result<Self, ()> {
-        let grid = input
-            .lines()
-            .flat_map(|line| line.chars())
-            .map(|c| match c {
-                '.' => Ok(Tile::Empty),
-                '/' => Ok(Tile::FMirror),
-                '\\' => Ok(Tile::BMirror),
-                '|' => Ok(Tile::VSplitter),
-                '-' => Ok(Tile::HSplitter),
-                _ => Err(()),
-            })
-            .collect::<Result<Vec<_>, _>>()?;
+    fn from_input(input: &str) -> Result<Self, grid::GridError> {
+        Ok(Grid::new(input.parse()?))
+    }
 
-        let size = input.lines().count();
-        Ok(Grid::new(grid, size))
+    fn get(&self, position: Point) -> Option<Tile> {
+        self.tiles.get(position).copied()
     }
 
-    fn get(&self, position: Position) -> Option<Tile> {
-        if position.x >= self.size || position.y >= self.size {
-            return None;
-        }
-        Some(self.tiles[position.y * self.size + position.x])
+    fn step(&self, position: Point, direction: Direction) -> Option<(Point, Direction)> {
+        (SignedPoint::from(position) + direction.delta())
+            .to_point(&self.tiles)
+            .map(|position| (position, direction))
     }
 
-    fn add_beam(&mut self, position: Position, direction: Direction) {
+    fn add_beam(&mut self, position: Point, direction: Direction) {
         self.beams.push((position, direction));
     }
 
@@ -176,37 +138,14 @@ impl Grid {
             .filter_map(|(pos, dir)| {
                 let tile = self.get(*pos).unwrap();
                 match tile.collision(*dir) {
-                    CollisionResult::Continue => match pos.move_direction(*dir).ok()? {
-                        Position { x, y } if x < self.size && y < self.size => {
-                            Some((Position { x, y }, *dir))
-                        }
-                        _ => None,
-                    },
-                    CollisionResult::Turn(new_dir) => match pos.move_direction(new_dir).ok()? {
-                        Position { x, y } if x < self.size && y < self.size => {
-                            Some((Position { x, y }, new_dir))
-                        }
-                        _ => None,
-                    },
+                    CollisionResult::Continue => self.step(*pos, *dir),
+                    CollisionResult::Turn(new_dir) => self.step(*pos, new_dir),
                     CollisionResult::Split(dir1, dir2) => {
-                        let new_beam = match pos.move_direction(dir1) {
-                            Ok(Position { x, y }) if x < self.size && y < self.size => {
-                                Some((Position { x, y }, dir1))
-                            }
-                            _ => None,
-                        };
-
-                        match new_beam {
-                            Some(b) => new_beams.push(b),
-                            _ => (),
-                        };
-
-                        match pos.move_direction(dir2).ok()? {
-                            Position { x, y } if x < self.size && y < self.size => {
-                                Some((Position { x, y }, dir2))
-                            }
-                            _ => None,
+                        if let Some(b) = self.step(*pos, dir1) {
+                            new_beams.push(b);
                         }
+
+                        self.step(*pos, dir2)
                     }
                 }
             })
@@ -216,29 +155,45 @@ impl Grid {
     }
 
     fn run_simulation(&mut self) -> u128 {
-        let mut seen = self.beams.iter().map(|b| *b).collect::<HashSet<_>>();
+        self.energize().count_ones() as u128
+    }
+
+    fn image(&self, energized: &BitGrid, scale: usize) -> Image {
+        Image::from_grid(&self.tiles, scale, |point, tile| {
+            tile_color(tile, energized.get(point))
+        })
+    }
+
+    fn energize(&mut self) -> BitGrid {
+        self.energize_with(|_, _| ())
+    }
+
+    /// Runs the beams until they stop visiting new states, calling `on_tick`
+    /// with the tiles energized so far before every tick.
+    fn energize_with<F>(&mut self, mut on_tick: F) -> BitGrid
+    where
+        F: FnMut(&Self, &BitGrid),
+    {
+        let (width, height) = (self.tiles.width(), self.tiles.height());
+        // One layer per orthogonal direction.
+        let mut seen = vec![BitGrid::new(width, height); Direction::ORTHOGONAL.len()];
+        let mut energized = BitGrid::new(width, height);
         loop {
+            self.beams
+                .retain(|(pos, dir)| seen[dir.index()].insert(*pos));
             if self.beams.is_empty() {
                 break;
             }
 
-            self.beams.iter().for_each(|b| {
-                seen.insert(*b);
+            self.beams.iter().for_each(|(pos, _)| {
+                energized.insert(*pos);
             });
 
+            on_tick(self, &energized);
             self.tick();
-            self.beams = self
-                .beams
-                .iter()
-                .filter(|b| !seen.contains(b))
-                .map(|b| *b)
-                .collect();
         }
 
-        seen.iter()
-            .map(|(pos, _)| pos)
-            .collect::<HashSet<_>>()
-            .len() as u128
+        energized
     }
 }
 
@@ -256,38 +211,28 @@ impl Puzzle {
 
 impl super::Puzzle for Puzzle {
     fn run_part_one(&self) -> Result<super::AOCResult, Box<dyn std::error::Error>> {
-        let mut grid = Grid::from_input(&self.0).map_err(|_| "Invalid input")?;
-        grid.add_beam(Position { x: 0, y: 0 }, Direction::Right);
+        let mut grid = Grid::from_input(&self.0)?;
+        grid.add_beam(Point(0, 0), Direction::Right);
 
         Ok(super::AOCResult::U128(grid.run_simulation()))
     }
 
     fn run_part_two(&self) -> Result<super::AOCResult, Box<dyn std::error::Error>> {
-        let grid = Grid::from_input(&self.0).map_err(|_| "Invalid input")?;
-        let res = (0..grid.size)
+        let grid = Grid::from_input(&self.0)?;
+        let (width, height) = (grid.tiles.width(), grid.tiles.height());
+        let res = (0..width.max(height))
             .filter_map(|i| {
-                let b1 = (Position { x: i, y: 0 }, Direction::Down);
-                let b2 = (
-                    Position {
-                        x: i,
-                        y: grid.size - 1,
-                    },
-                    Direction::Up,
-                );
-                let b3 = (Position { x: 0, y: i }, Direction::Right);
-                let b4 = (
-                    Position {
-                        x: grid.size - 1,
-                        y: i,
-                    },
-                    Direction::Left,
-                );
+                let b1 = (i < width).then_some((Point(i, 0), Direction::Down));
+                let b2 = (i < width).then(|| (Point(i, height - 1), Direction::Up));
+                let b3 = (i < height).then_some((Point(0, i), Direction::Right));
+                let b4 = (i < height).then(|| (Point(width - 1, i), Direction::Left));
                 [b1, b2, b3, b4]
                     .iter()
-                    .filter_map(|b| {
+                    .flatten()
+                    .map(|b| {
                         let mut grid = grid.clone();
                         grid.add_beam(b.0, b.1);
-                        Some(grid.run_simulation())
+                        grid.run_simulation()
                     })
                     .max()
             })
@@ -295,11 +240,44 @@ impl super::Puzzle for Puzzle {
             .ok_or("No solution")?;
         Ok(super::AOCResult::U128(res))
     }
+
+    fn image(&self, scale: usize) -> Option<Result<Image, Box<dyn std::error::Error>>> {
+        let mut grid = match Grid::from_input(&self.0) {
+            Ok(grid) => grid,
+            Err(e) => return Some(Err(e.into())),
+        };
+        grid.add_beam(Point(0, 0), Direction::Right);
+        let energized = grid.energize();
+
+        Some(Ok(grid.image(&energized, scale)))
+    }
+
+    fn animate(&self, recorder: &mut Recorder) -> Option<Result<(), Box<dyn std::error::Error>>> {
+        let mut grid = match Grid::from_input(&self.0) {
+            Ok(grid) => grid,
+            Err(e) => return Some(Err(e.into())),
+        };
+        grid.add_beam(Point(0, 0), Direction::Right);
+        grid.energize_with(|grid, energized| {
+            let beams = grid
+                .beams
+                .iter()
+                .map(|(pos, _)| *pos)
+                .collect::<HashSet<_>>();
+            recorder.record(&grid.tiles, |point, tile| match beams.contains(&point) {
+                true => Rgb(230, 50, 50),
+                false => tile_color(tile, energized.get(point)),
+            });
+        });
+
+        Some(Ok(()))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::render::Color;
 
     const SAMPLE: &str = r#".|...\....
 |.-.\.....
@@ -315,21 +293,65 @@ mod tests {
     #[test]
     fn test_part_one_sample() {
         let mut g = Grid::from_input(SAMPLE).unwrap();
-        g.add_beam(Position { x: 0, y: 0 }, Direction::Right);
+        g.add_beam(Point(0, 0), Direction::Right);
         assert_eq!(g.beams.len(), 1);
-        assert_eq!(g.beams[0].0, Position { x: 0, y: 0 });
+        assert_eq!(g.beams[0].0, Point(0, 0));
         assert_eq!(g.beams[0].1, Direction::Right);
         g.tick();
         assert_eq!(g.beams.len(), 1);
-        assert_eq!(g.beams[0].0, Position { x: 1, y: 0 });
+        assert_eq!(g.beams[0].0, Point(1, 0));
         assert_eq!(g.beams[0].1, Direction::Right);
         g.tick();
         assert_eq!(g.beams.len(), 1);
-        assert_eq!(g.beams[0].0, Position { x: 1, y: 1 });
+        assert_eq!(g.beams[0].0, Point(1, 1));
         assert_eq!(g.beams[0].1, Direction::Down);
 
         let mut g = Grid::from_input(SAMPLE).unwrap();
-        g.add_beam(Position { x: 0, y: 0 }, Direction::Right);
+        g.add_beam(Point(0, 0), Direction::Right);
         assert_eq!(g.run_simulation(), 46);
     }
+
+    #[test]
+    fn test_image() {
+        let mut g = Grid::from_input(SAMPLE).unwrap();
+        g.add_beam(Point(0, 0), Direction::Right);
+        let energized = g.energize();
+        let image = g.image(&energized, 3);
+        assert_eq!((image.width(), image.height()), (30, 30));
+        assert_eq!(image.pixel(0, 0), Some(Rgb(250, 200, 40)));
+        assert_eq!(image.pixel(3, 2), Some(Rgb(255, 240, 160)));
+        assert_eq!(image.pixel(29, 29), Some(Rgb::BLACK));
+        let lit = image.pixels().iter().filter(|p| **p != Rgb::BLACK).count();
+        assert_eq!(lit, 9 * (46 + 8));
+    }
+
+    #[test]
+    fn test_render_energized() {
+        let mut g = Grid::from_input(SAMPLE).unwrap();
+        g.add_beam(Point(0, 0), Direction::Right);
+        let energized = g.energize();
+        let map = Renderer::new(&g.tiles, |_, _| '.')
+            .colors(false)
+            .highlight(energized.iter_ones(), Some('#'), None)
+            .to_string();
+        assert_eq!(
+            map,
+            r#"######....
+.#...#....
+.#...#####
+.#...##...
+.#...##...
+.#...##...
+.#..####..
+########..
+.#######..
+.#...#.#.."#
+        );
+        let colored = Renderer::new(&g.tiles, |_, tile| tile.glyph())
+            .colors(true)
+            .highlight(energized.iter_ones(), None, Some(Color::Yellow))
+            .to_string();
+        assert!(colored.starts_with("\x1b[33m.\x1b[0m\x1b[33m|\x1b[0m"));
+        assert!(colored.ends_with("\x1b[33m.\x1b[0m.."));
+    }
 }
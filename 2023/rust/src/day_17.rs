@@ -1,7 +1,7 @@
 use crate::grid::*;
-use std::collections::{BTreeMap, BTreeSet};
+use crate::search;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 struct Node {
     point: Point,
     count: usize,
@@ -16,118 +16,43 @@ impl Node {
             count,
         }
     }
-
-    fn as_index(&self) -> usize {
-        let direction = match self.direction {
-            Direction::Up => 0,
-            Direction::Down => 1,
-            Direction::Left => 2,
-            Direction::Right => 3,
-        };
-        direction * 10 + self.count - 1
-    }
 }
 
-fn djikstra(grid: &Grid<usize>, ultra: bool) -> Option<usize> {
-    let mut visited_nodes = vec![vec![[false; 40]; grid.size()]; grid.size()];
-    let mut unvisited_nodes = BTreeMap::<usize, BTreeSet<Node>>::new();
-    unvisited_nodes.insert(
-        *grid.get(Point(0, 1)).unwrap(),
-        BTreeSet::from_iter(vec![Node::new(Point(0, 1), Direction::Down, 1)]),
-    );
-    unvisited_nodes.insert(
-        *grid.get(Point(1, 0)).unwrap(),
-        BTreeSet::from_iter(vec![Node::new(Point(1, 0), Direction::Right, 1)]),
-    );
-
-    loop {
-        let (current_node, current_cost) = {
-            let mut min_value = match unvisited_nodes.first_entry() {
-                Some(min_value) => min_value,
-                None => {
-                    break;
-                }
-            };
-            let cost = *min_value.key();
-            let node = match min_value.get_mut().pop_last() {
-                Some(node) => node,
-                _ => {
-                    break;
-                }
-            };
-
-            if min_value.get().is_empty() {
-                min_value.remove_entry();
-            }
-
-            (node, cost)
-        };
-        if current_node.point.0 == grid.size() - 1 && current_node.point.1 == grid.size() - 1 {
-            return Some(current_cost);
-        }
-
-        [
-            Direction::Up,
-            Direction::Down,
-            Direction::Left,
-            Direction::Right,
-        ]
-        .iter()
-        .filter(|direction| match (**direction, current_node.direction) {
-            (Direction::Up, Direction::Down) => false,
-            (Direction::Down, Direction::Up) => false,
-            (Direction::Left, Direction::Right) => false,
-            (Direction::Right, Direction::Left) => false,
-            _ => true,
-        })
-        .filter_map(|direction| {
-            if direction == &current_node.direction {
-                if ultra {
-                    if current_node.count < 10 {
-                        Some((*direction, current_node.count + 1))
+fn minimal_heat_loss(grid: &Grid<usize>, start: Point, goal: Point, ultra: bool) -> Option<usize> {
+    let (min_run, max_run) = if ultra { (4, 10) } else { (1, 3) };
+    let starts =
+        [Direction::Right, Direction::Down].map(|direction| Node::new(start, direction, 0));
+
+    search::dijkstra_buckets(
+        starts,
+        |node| {
+            Direction::orthogonal()
+                .filter(|direction| *direction != node.direction.opposite())
+                .filter_map(|direction| {
+                    let count = if direction == node.direction {
+                        node.count + 1
+                    } else if node.count >= min_run {
+                        1
                     } else {
-                        None
+                        return None;
+                    };
+                    if count > max_run {
+                        return None;
                     }
-                } else if current_node.count >= 3 {
-                    None
-                } else {
-                    Some((*direction, current_node.count + 1))
-                }
-            } else if ultra && current_node.count < 4 {
-                None
-            } else {
-                Some((*direction, 1))
-            }
-        })
-        .filter_map(|(direction, count)| {
-            current_node
-                .point
-                .move_direction(direction)
-                .and_then(|point| {
-                    grid.get(point).map(|v| {
-                        (
-                            Node {
-                                point,
-                                direction,
-                                count,
-                            },
-                            v + current_cost,
-                        )
-                    })
+
+                    let point = node.point.move_direction(direction)?;
+                    grid.get(point)
+                        .map(|cost| (Node::new(point, direction, count), *cost))
                 })
-        })
-        .filter(|(node, _)| visited_nodes[node.point.0][node.point.1][node.as_index()] == false)
-        .for_each(|(node, value)| {
-            unvisited_nodes
-                .entry(value)
-                .or_insert_with(BTreeSet::new)
-                .insert(node);
-        });
-
-        visited_nodes[current_node.point.0][current_node.point.1][current_node.as_index()] = true;
-    }
+                .collect::<Vec<_>>()
+        },
+        |node| node.point == goal && node.count >= min_run,
+    )
+    .map(|path| path.cost)
+}
 
-    None
+fn bottom_right(grid: &Grid<usize>) -> Point {
+    Point(grid.width() - 1, grid.height() - 1)
 }
 
 pub struct Puzzle(String);
@@ -144,18 +69,8 @@ impl Puzzle {
 
 impl super::Puzzle for Puzzle {
     fn run_part_one(&self) -> Result<super::AOCResult, Box<dyn std::error::Error>> {
-        let blocks = self
-            .0
-            .lines()
-            .map(|line| {
-                line.chars()
-                    .map(|c| c.to_digit(10).expect("Not a digit") as usize)
-                    .collect::<Vec<_>>()
-            })
-            .collect::<Vec<_>>();
-
-        let grid = Grid::<_>::new(blocks.len(), blocks);
-        let res = djikstra(&grid, false)
+        let grid = Grid::parse_with(&self.0, |c| c.to_digit(10).map(|d| d as usize))?;
+        let res = minimal_heat_loss(&grid, Point(0, 0), bottom_right(&grid), false)
             .ok_or("No path found")
             .map(|v| super::AOCResult::USize(v))?;
 
@@ -163,18 +78,8 @@ impl super::Puzzle for Puzzle {
     }
 
     fn run_part_two(&self) -> Result<super::AOCResult, Box<dyn std::error::Error>> {
-        let blocks = self
-            .0
-            .lines()
-            .map(|line| {
-                line.chars()
-                    .map(|c| c.to_digit(10).expect("Not a digit") as usize)
-                    .collect::<Vec<_>>()
-            })
-            .collect::<Vec<_>>();
-
-        let grid = Grid::<_>::new(blocks.len(), blocks);
-        let res = djikstra(&grid, true)
+        let grid = Grid::parse_with(&self.0, |c| c.to_digit(10).map(|d| d as usize))?;
+        let res = minimal_heat_loss(&grid, Point(0, 0), bottom_right(&grid), true)
             .ok_or("No path found")
             .map(|v| super::AOCResult::USize(v))?;
 
@@ -202,18 +107,27 @@ mod tests {
 
     #[test]
     fn test() {
-        let blocks = SAMPLE_INPUT
-            .lines()
-            .map(|line| {
-                line.chars()
-                    .map(|c| c.to_digit(10).expect("Not a digit") as usize)
-                    .collect::<Vec<_>>()
-            })
-            .collect::<Vec<_>>();
-
-        let grid = Grid::<_>::new(SAMPLE_INPUT.lines().count(), blocks);
-        let res = djikstra(&grid, false);
+        let grid = Grid::parse_with(SAMPLE_INPUT, |c| c.to_digit(10).map(|d| d as usize)).unwrap();
+        let res = minimal_heat_loss(&grid, Point(0, 0), bottom_right(&grid), false);
         assert!(res.is_some());
         assert_eq!(res.unwrap(), 102);
+
+        let res = minimal_heat_loss(&grid, Point(0, 0), bottom_right(&grid), true);
+        assert_eq!(res, Some(94));
+    }
+
+    #[test]
+    fn test_ultra_crucible_stopping_distance() {
+        let grid = Grid::parse_with(
+            "111111111111
+999999999991
+999999999991
+999999999991
+999999999991",
+            |c| c.to_digit(10).map(|d| d as usize),
+        )
+        .unwrap();
+        let res = minimal_heat_loss(&grid, Point(0, 0), bottom_right(&grid), true);
+        assert_eq!(res, Some(71));
     }
 }
@@ -1,3 +1,11 @@
+use crate::grid::{Direction, FromChar, SignedPoint};
+use crate::image::{Image, Rgb};
+use crate::polygon::Polygon;
+use crate::sparse::SparseGrid;
+
+/// Largest bounding box `Puzzle::image` will turn into a dense grid.
+const MAX_IMAGE_CELLS: usize = 4_000_000;
+
 pub struct Puzzle(String);
 
 impl Puzzle {
@@ -18,7 +26,7 @@ impl super::Puzzle for Puzzle {
             .map(|s| Instruction::part_one_parse(s).unwrap())
             .collect::<Vec<_>>();
 
-        let res = cubic_meters(&build_instructions);
+        let res = cubic_meters(&build_instructions).ok_or("Lagoon is too large to measure")?;
 
         Ok(super::AOCResult::U128(res))
     }
@@ -30,24 +38,35 @@ impl super::Puzzle for Puzzle {
             .map(|s| Instruction::part_two_parse(s).unwrap())
             .collect::<Vec<_>>();
 
-        let res = cubic_meters(&build_instructions);
+        let res = cubic_meters(&build_instructions).ok_or("Lagoon is too large to measure")?;
 
         Ok(super::AOCResult::U128(res))
     }
-}
 
-#[derive(Debug, PartialEq)]
-enum Direction {
-    Up,
-    Down,
-    Left,
-    Right,
+    fn image(&self, scale: usize) -> Option<Result<Image, Box<dyn std::error::Error>>> {
+        let build_instructions = self
+            .0
+            .lines()
+            .map(Instruction::part_one_parse)
+            .collect::<Result<Vec<_>, _>>();
+        let Ok(build_instructions) = build_instructions else {
+            return Some(Err("Invalid instruction".into()));
+        };
+
+        let Some(trench) = dig_trench(&build_instructions).to_grid(MAX_IMAGE_CELLS) else {
+            return Some(Err("Lagoon is too large to draw".into()));
+        };
+        Some(Ok(Image::from_grid(&trench, scale, |_, cell| match cell {
+            Some(_) => Rgb(160, 90, 40),
+            None => Rgb::BLACK,
+        })))
+    }
 }
 
 #[derive(Debug)]
 struct Instruction {
     direction: Direction,
-    distance: i32,
+    distance: i64,
 }
 
 #[derive(Debug)]
@@ -60,14 +79,13 @@ impl Instruction {
             .map(|s| s.split_at(5))
             .expect("No color code");
 
-        let distance = i32::from_str_radix(distance, 16).map_err(|_| ParseInstructionError)?;
-        let direction = match direction {
-            "0" => Direction::Right,
-            "1" => Direction::Down,
-            "2" => Direction::Left,
-            "3" => Direction::Up,
-            _ => return Err(ParseInstructionError),
-        };
+        let distance = i64::from_str_radix(distance, 16).map_err(|_| ParseInstructionError)?;
+        let direction = direction
+            .chars()
+            .next()
+            .filter(char::is_ascii_digit)
+            .and_then(Direction::from_char)
+            .ok_or(ParseInstructionError)?;
 
         Ok(Self {
             direction,
@@ -77,19 +95,18 @@ impl Instruction {
 
     fn part_one_parse(s: &str) -> Result<Self, ParseInstructionError> {
         let (direction, rest) = s.split_at(1);
-        let direction = match direction {
-            "U" => Ok(Direction::Up),
-            "D" => Ok(Direction::Down),
-            "L" => Ok(Direction::Left),
-            "R" => Ok(Direction::Right),
-            _ => Err(ParseInstructionError),
-        }?;
+        let direction = direction
+            .chars()
+            .next()
+            .filter(|c| "UDLR".contains(*c))
+            .and_then(Direction::from_char)
+            .ok_or(ParseInstructionError)?;
 
         let rest = rest.trim();
         let mid = rest.trim().find(' ').ok_or(ParseInstructionError)?;
         let (distance, _) = rest.split_at(mid);
 
-        let distance = distance.parse::<i32>().map_err(|_| ParseInstructionError)?;
+        let distance = distance.parse::<i64>().map_err(|_| ParseInstructionError)?;
 
         Ok(Self {
             direction,
@@ -98,44 +115,26 @@ impl Instruction {
     }
 }
 
-fn cubic_meters(instructions: &[Instruction]) -> u128 {
-    let vertices = instructions
-        .iter()
-        .fold(
-            (
-                Vec::<(i32, i32)>::with_capacity(instructions.len() - 1),
-                (0, 0),
-            ),
-            |(mut v, p),
-             Instruction {
-                 direction,
-                 distance,
-             }| {
-                v.push(p);
-                match direction {
-                    Direction::Up => (v, (p.0, p.1 - distance)),
-                    Direction::Down => (v, (p.0, p.1 + distance)),
-                    Direction::Left => (v, (p.0 - distance, p.1)),
-                    Direction::Right => (v, (p.0 + distance, p.1)),
-                }
-            },
-        )
-        .0;
-
-    let sum = vertices.windows(2).fold(0i128, |acc, w| {
-        let x1 = w[0].0;
-        let x2 = w[1].0;
-        let y1 = w[0].1;
-        let y2 = w[1].1;
-        acc + ((x2 + x1) as i128 * (y2 - y1) as i128)
+fn dig_trench(instructions: &[Instruction]) -> SparseGrid<()> {
+    let mut trench = SparseGrid::new();
+    let mut position = SignedPoint(0, 0);
+    trench.insert(position, ());
+    instructions.iter().for_each(|instruction| {
+        (0..instruction.distance).for_each(|_| {
+            position = position.move_direction(instruction.direction);
+            trench.insert(position, ());
+        });
     });
+    trench
+}
 
-    let area = sum.abs() as u128 >> 1;
-    let boundary_points = instructions
-        .iter()
-        .fold(0, |acc, i| acc + i.distance as u128);
+fn cubic_meters(instructions: &[Instruction]) -> Option<u128> {
+    let lagoon = Polygon::from_steps(
+        SignedPoint(0, 0),
+        instructions.iter().map(|i| (i.direction, i.distance)),
+    )?;
 
-    area + (boundary_points >> 1) + 1
+    lagoon.enclosed_points()
 }
 
 #[cfg(test)]
@@ -171,7 +170,7 @@ U 2 (#7a21e3)";
             .map(|s| Instruction::part_one_parse(s).unwrap())
             .collect::<Vec<_>>();
 
-        assert_eq!(cubic_meters(&build_instructions), 62);
+        assert_eq!(cubic_meters(&build_instructions), Some(62));
     }
 
     #[test]
@@ -181,6 +180,41 @@ U 2 (#7a21e3)";
             .map(|s| Instruction::part_two_parse(s).unwrap())
             .collect::<Vec<_>>();
 
-        assert_eq!(cubic_meters(&build_instructions), 952408144115);
+        assert_eq!(cubic_meters(&build_instructions), Some(952408144115));
+    }
+
+    #[test]
+    fn test_out_and_back() {
+        let build_instructions = ["R 4 (#000040)", "L 4 (#000042)"]
+            .iter()
+            .map(|s| Instruction::part_one_parse(s).unwrap())
+            .collect::<Vec<_>>();
+
+        assert_eq!(cubic_meters(&build_instructions), Some(5));
+        assert_eq!(cubic_meters(&[]), Some(1));
+    }
+
+    #[test]
+    fn test_dig_trench() {
+        let build_instructions = SAMPLE_INPUT
+            .lines()
+            .map(|s| Instruction::part_one_parse(s).unwrap())
+            .collect::<Vec<_>>();
+        let trench = dig_trench(&build_instructions);
+
+        assert_eq!(trench.len(), 38);
+        assert_eq!(
+            trench.render('.', |_| '#'),
+            "#######
+#.....#
+###...#
+..#...#
+..#...#
+###.###
+#...#..
+##..###
+.#....#
+.######"
+        );
     }
 }
@@ -1,3 +1,6 @@
+use crate::graph::Graph;
+use crate::hyperrect::{DisjointUnion, HyperRect};
+use crate::interval::Interval;
 use std::cmp::Ordering;
 
 #[derive(Debug, Clone, Copy)]
@@ -18,6 +21,15 @@ impl std::str::FromStr for Order {
     }
 }
 
+impl Order {
+    fn symbol(&self) -> char {
+        match self {
+            Self::Less => '<',
+            Self::Greater => '>',
+        }
+    }
+}
+
 impl PartialEq<Ordering> for Order {
     fn eq(&self, other: &Ordering) -> bool {
         match (self, other) {
@@ -43,10 +55,21 @@ enum Then {
     Rejected,
 }
 
-enum RangeApplyResult {
-    None,
-    All(Then),
-    Split(([(u16, u16); 4], Then), [(u16, u16); 4]),
+impl Then {
+    fn target(&self) -> String {
+        match self {
+            Then::Next(id) => format_id(*id),
+            Then::Accepted => "A".to_string(),
+            Then::Rejected => "R".to_string(),
+        }
+    }
+}
+
+const CATEGORIES: [&str; 4] = ["x", "m", "a", "s"];
+
+/// Every part with ratings from 1 to 4000 inclusive.
+fn all_parts() -> HyperRect {
+    HyperRect::uniform(&CATEGORIES, Interval::new(1, 4001))
 }
 
 #[derive(Debug)]
@@ -69,47 +92,21 @@ impl Rule {
         }
     }
 
-    fn apply_rule_to_parts(&self, parts: &[(u16, u16); 4]) -> RangeApplyResult {
+    /// Splits `parts` into the ones this rule sends to its target and the
+    /// ones that fall through to the next rule.
+    fn apply_rule_to_parts(
+        &self,
+        parts: &HyperRect,
+    ) -> (Option<(HyperRect, Then)>, Option<HyperRect>) {
         match self {
-            Rule::Else(t) => RangeApplyResult::All(*t),
-            Rule::If(c, o, v, t) => {
-                let r = parts[*c as usize];
-                match (o, r.0.cmp(&v), r.1.cmp(&v)) {
-                    (_, Ordering::Greater, _)
-                    | (_, _, Ordering::Less)
-                    | (_, Ordering::Equal, Ordering::Equal) => RangeApplyResult::None,
-                    (Order::Greater, _, Ordering::Equal) => RangeApplyResult::None,
-                    (Order::Less, Ordering::Equal, _) => RangeApplyResult::None,
-                    (Order::Less, Ordering::Less, Ordering::Greater)
-                    | (Order::Less, _, Ordering::Equal) => {
-                        let left = {
-                            let mut ranges = parts.clone();
-                            ranges[*c as usize] = (r.0, v - 1);
-                            ranges
-                        };
-                        let right = {
-                            let mut ranges = parts.clone();
-                            ranges[*c as usize] = (*v, r.1);
-                            ranges
-                        };
-
-                        RangeApplyResult::Split((left, *t), right)
-                    }
-                    (_, Ordering::Less, Ordering::Greater) | (_, Ordering::Equal, _) => {
-                        let left = {
-                            let mut ranges = parts.clone();
-                            ranges[*c as usize] = (r.0, *v);
-                            ranges
-                        };
-                        let right = {
-                            let mut ranges = parts.clone();
-                            ranges[*c as usize] = (v + 1, r.1);
-                            ranges
-                        };
-
-                        RangeApplyResult::Split((right, *t), left)
-                    }
-                }
+            Rule::Else(t) => (Some((parts.clone(), *t)), None),
+            Rule::If(c, Order::Less, v, t) => {
+                let (matched, rest) = parts.split(*c as usize, *v as i64);
+                (matched.map(|m| (m, *t)), rest)
+            }
+            Rule::If(c, Order::Greater, v, t) => {
+                let (rest, matched) = parts.split(*c as usize, *v as i64 + 1);
+                (matched.map(|m| (m, *t)), rest)
             }
         }
     }
@@ -124,6 +121,19 @@ fn parse_id(s: &str) -> u16 {
     u16::from_str_radix(s, 36).expect(format!("Unable to parse id {}", s).as_str())
 }
 
+fn format_id(id: u16) -> String {
+    let mut digits = vec![];
+    let mut rest = id as u32;
+    loop {
+        digits.push(char::from_digit(rest % 36, 36).expect("Base 36 digit"));
+        rest /= 36;
+        if rest == 0 {
+            break;
+        }
+    }
+    digits.iter().rev().collect()
+}
+
 fn parse_rules(rules: &str) -> Vec<Rule> {
     let rules = rules
         .strip_prefix("{")
@@ -257,39 +267,64 @@ impl Aplenty {
             .fold(0u128, |acc, p| acc + (p[0] + p[1] + p[2] + p[3]) as u128)
     }
 
-    fn acceptable_combinations(&self, mut part_ranges: [(u16, u16); 4], workflow_id: u16) -> u128 {
+    fn accepted_parts(&self, parts: HyperRect, workflow_id: u16, accepted: &mut DisjointUnion) {
         let workflow = self
             .workflows
             .binary_search_by(|(id, _)| id.cmp(&workflow_id))
             .map(|i| &self.workflows[i].1)
             .expect("No starting point");
 
-        let mut combinations = 0u128;
+        let mut remaining = Some(parts);
         for rule in workflow {
-            match rule.apply_rule_to_parts(&part_ranges) {
-                RangeApplyResult::None => (),
-                RangeApplyResult::All(t) => {
-                    combinations += match t {
-                        Then::Rejected => 0,
-                        Then::Accepted => part_ranges
-                            .iter()
-                            .fold(1, |c, (a, b)| c * (b - a + 1) as u128),
-                        Then::Next(next_id) => self.acceptable_combinations(part_ranges, next_id),
-                    };
-                    break;
-                }
-                RangeApplyResult::Split((r0, t), r1) => {
-                    part_ranges = r1;
-                    combinations += match t {
-                        Then::Rejected => 0,
-                        Then::Accepted => r0.iter().fold(1, |c, (a, b)| c * (b - a + 1) as u128),
-                        Then::Next(next_id) => self.acceptable_combinations(r0, next_id),
-                    };
-                }
+            let Some(parts) = remaining.take() else {
+                break;
             };
+            let (matched, rest) = rule.apply_rule_to_parts(&parts);
+            match matched {
+                Some((m, Then::Accepted)) => accepted.insert(m),
+                Some((m, Then::Next(next_id))) => self.accepted_parts(m, next_id, accepted),
+                Some((_, Then::Rejected)) | None => (),
+            }
+            remaining = rest;
+        }
+    }
+
+    /// Workflows pointing at the workflows their rules send parts to, with
+    /// each edge labelled by the rule's condition.
+    fn graph(&self) -> Graph {
+        let mut graph = Graph::new();
+        for (id, rules) in &self.workflows {
+            let from = format_id(*id);
+            for rule in rules {
+                let (label, then) = match rule {
+                    Rule::If(c, o, v, t) => (
+                        Some(format!("{}{}{}", CATEGORIES[*c as usize], o.symbol(), v)),
+                        t,
+                    ),
+                    Rule::Else(t) => (None, t),
+                };
+                graph.add_labelled_edge(&from, &then.target(), label);
+            }
         }
 
-        combinations
+        [
+            ("in", "shape=box"),
+            ("A", "color=green"),
+            ("R", "color=red"),
+        ]
+        .into_iter()
+        .for_each(|(name, attributes)| {
+            if let Some(id) = graph.node(name) {
+                graph.set_attributes(id, attributes);
+            }
+        });
+        graph
+    }
+
+    fn acceptable_combinations(&self, parts: HyperRect, workflow_id: u16) -> u128 {
+        let mut accepted = DisjointUnion::new();
+        self.accepted_parts(parts, workflow_id, &mut accepted);
+        accepted.volume()
     }
 }
 
@@ -315,10 +350,14 @@ impl super::Puzzle for Puzzle {
 
     fn run_part_two(&self) -> Result<super::AOCResult, Box<dyn std::error::Error>> {
         let a = Aplenty::from_input(&self.0);
-        let res = a.acceptable_combinations([(1, 4000); 4], parse_id("in"));
+        let res = a.acceptable_combinations(all_parts(), parse_id("in"));
 
         Ok(crate::AOCResult::U128(res))
     }
+
+    fn graph(&self) -> Option<Result<Graph, Box<dyn std::error::Error>>> {
+        Some(Ok(Aplenty::from_input(&self.0).graph()))
+    }
 }
 
 #[cfg(test)]
@@ -353,7 +392,26 @@ hdj{m>838:A,pv}
     #[test]
     fn test_part_two() {
         let a = Aplenty::from_input(SAMPLE_INPUT);
-        let res = a.acceptable_combinations([(1, 4000); 4], parse_id("in"));
+        let res = a.acceptable_combinations(all_parts(), parse_id("in"));
         assert_eq!(res, 167409079868000);
     }
+
+    #[test]
+    fn test_format_id() {
+        for name in ["in", "px", "qqz", "a", "zzz"] {
+            assert_eq!(format_id(parse_id(name)), name);
+        }
+    }
+
+    #[test]
+    fn test_graph() {
+        let graph = Aplenty::from_input(SAMPLE_INPUT).graph();
+        assert_eq!(graph.len(), 13);
+        let start = graph.node("in").unwrap();
+        assert_eq!(graph.reachable_from(start).len(), 13);
+        assert!(graph.topological_sort().is_ok());
+        assert!(graph
+            .to_dot("workflows")
+            .contains("    \"in\" -> \"px\" [label=\"s<1351\"];\n    \"in\" -> \"qqz\";\n"));
+    }
 }
@@ -0,0 +1,360 @@
+use crate::interner::Interner;
+use std::collections::VecDeque;
+use std::fmt::Write;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Edge {
+    pub to: usize,
+    pub label: Option<String>,
+}
+
+/// A directed graph whose nodes are named by strings and numbered densely
+/// in the order they first appear.
+#[derive(Debug, Clone, Default)]
+pub struct Graph {
+    nodes: Interner,
+    edges: Vec<Vec<Edge>>,
+    attributes: Vec<Option<String>>,
+}
+
+impl Graph {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    pub fn add_node(&mut self, name: &str) -> usize {
+        let id = self.nodes.intern(name);
+        self.edges.resize(self.nodes.len(), vec![]);
+        self.attributes.resize(self.nodes.len(), None);
+        id
+    }
+
+    pub fn add_edge(&mut self, from: &str, to: &str) -> (usize, usize) {
+        self.add_labelled_edge(from, to, None)
+    }
+
+    pub fn add_labelled_edge(
+        &mut self,
+        from: &str,
+        to: &str,
+        label: Option<String>,
+    ) -> (usize, usize) {
+        let (from, to) = (self.add_node(from), self.add_node(to));
+        self.edges[from].push(Edge { to, label });
+        (from, to)
+    }
+
+    /// Extra Graphviz attributes for a node, e.g. `shape=box`.
+    pub fn set_attributes(&mut self, id: usize, attributes: &str) {
+        self.attributes[id] = Some(attributes.to_string());
+    }
+
+    pub fn node(&self, name: &str) -> Option<usize> {
+        self.nodes.get(name)
+    }
+
+    pub fn name(&self, id: usize) -> &str {
+        self.nodes.name(id).expect("Unknown node id")
+    }
+
+    pub fn edges(&self, id: usize) -> &[Edge] {
+        &self.edges[id]
+    }
+
+    pub fn successors(&self, id: usize) -> impl Iterator<Item = usize> + '_ {
+        self.edges[id].iter().map(|e| e.to)
+    }
+
+    fn predecessors(&self) -> Vec<Vec<usize>> {
+        let mut predecessors = vec![vec![]; self.len()];
+        (0..self.len()).for_each(|from| {
+            self.successors(from)
+                .for_each(|to| predecessors[to].push(from))
+        });
+        predecessors
+    }
+
+    /// Orders the nodes so every edge points forward, or returns the nodes
+    /// of one cycle, in edge order, if there is none.
+    pub fn topological_sort(&self) -> Result<Vec<usize>, Vec<usize>> {
+        let predecessors = self.predecessors();
+        let mut in_degree = predecessors.iter().map(Vec::len).collect::<Vec<_>>();
+        let mut ready = (0..self.len())
+            .filter(|&id| in_degree[id] == 0)
+            .collect::<VecDeque<_>>();
+        let mut order = vec![];
+        while let Some(id) = ready.pop_front() {
+            order.push(id);
+            self.successors(id).for_each(|next| {
+                in_degree[next] -= 1;
+                if in_degree[next] == 0 {
+                    ready.push_back(next);
+                }
+            });
+        }
+
+        if order.len() == self.len() {
+            return Ok(order);
+        }
+
+        // Every node left over still has a left-over predecessor, so walking
+        // backwards through them must eventually revisit one.
+        let start = (0..self.len())
+            .find(|&id| in_degree[id] > 0)
+            .expect("Unsorted nodes always have a remaining in-degree");
+        let mut position = vec![None; self.len()];
+        let mut walk = vec![];
+        let mut id = start;
+        while position[id].is_none() {
+            position[id] = Some(walk.len());
+            walk.push(id);
+            id = *predecessors[id]
+                .iter()
+                .find(|&&p| in_degree[p] > 0)
+                .expect("Left-over node without a left-over predecessor");
+        }
+
+        let mut cycle = walk.split_off(position[id].expect("Walk ended on a visited node"));
+        cycle.reverse();
+        Err(cycle)
+    }
+
+    /// Tarjan's algorithm, with an explicit stack so deep graphs don't
+    /// overflow. Components come out in reverse topological order.
+    pub fn strongly_connected_components(&self) -> Vec<Vec<usize>> {
+        let mut index = vec![None; self.len()];
+        let mut low_link = vec![0; self.len()];
+        let mut on_stack = vec![false; self.len()];
+        let mut stack = vec![];
+        let mut components = vec![];
+        let mut next_index = 0;
+
+        for root in 0..self.len() {
+            if index[root].is_some() {
+                continue;
+            }
+
+            // Each frame is a node and how many of its edges were followed.
+            let mut frames = vec![(root, 0)];
+            while let Some(&mut (id, ref mut edge)) = frames.last_mut() {
+                if *edge == 0 && index[id].is_none() {
+                    index[id] = Some(next_index);
+                    low_link[id] = next_index;
+                    next_index += 1;
+                    stack.push(id);
+                    on_stack[id] = true;
+                }
+
+                if let Some(next) = self.edges[id].get(*edge).map(|e| e.to) {
+                    *edge += 1;
+                    match index[next] {
+                        None => frames.push((next, 0)),
+                        Some(i) if on_stack[next] => low_link[id] = low_link[id].min(i),
+                        Some(_) => (),
+                    }
+                    continue;
+                }
+
+                frames.pop();
+                if let Some(&(parent, _)) = frames.last() {
+                    low_link[parent] = low_link[parent].min(low_link[id]);
+                }
+                if Some(low_link[id]) == index[id] {
+                    let mut component = vec![];
+                    while let Some(member) = stack.pop() {
+                        on_stack[member] = false;
+                        component.push(member);
+                        if member == id {
+                            break;
+                        }
+                    }
+                    components.push(component);
+                }
+            }
+        }
+
+        components
+    }
+
+    /// Nodes reachable from `start`, itself included, in breadth-first
+    /// order.
+    pub fn reachable_from(&self, start: usize) -> Vec<usize> {
+        let mut seen = vec![false; self.len()];
+        seen[start] = true;
+        let mut order = vec![start];
+        let mut next = 0;
+        while let Some(&id) = order.get(next) {
+            next += 1;
+            self.successors(id).for_each(|to| {
+                if !seen[to] {
+                    seen[to] = true;
+                    order.push(to);
+                }
+            });
+        }
+        order
+    }
+
+    pub fn is_reachable(&self, from: usize, to: usize) -> bool {
+        self.reachable_from(from).contains(&to)
+    }
+
+    /// Graphviz source for the graph, e.g. for `dot -Tsvg`.
+    pub fn to_dot(&self, name: &str) -> String {
+        let mut dot = format!("digraph {} {{\n", quote(name));
+        for id in 0..self.len() {
+            match &self.attributes[id] {
+                Some(attributes) => {
+                    writeln!(dot, "    {} [{}];", quote(self.name(id)), attributes)
+                }
+                None => writeln!(dot, "    {};", quote(self.name(id))),
+            }
+            .expect("Writing to a String can't fail");
+        }
+        for id in 0..self.len() {
+            for edge in &self.edges[id] {
+                let (from, to) = (quote(self.name(id)), quote(self.name(edge.to)));
+                match &edge.label {
+                    Some(label) => {
+                        writeln!(dot, "    {} -> {} [label={}];", from, to, quote(label))
+                    }
+                    None => writeln!(dot, "    {} -> {};", from, to),
+                }
+                .expect("Writing to a String can't fail");
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+fn quote(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+impl<S: AsRef<str>> FromIterator<(S, S)> for Graph {
+    fn from_iter<I: IntoIterator<Item = (S, S)>>(iter: I) -> Self {
+        let mut graph = Self::new();
+        iter.into_iter().for_each(|(from, to)| {
+            graph.add_edge(from.as_ref(), to.as_ref());
+        });
+        graph
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(graph: &Graph, ids: &[usize]) -> Vec<String> {
+        ids.iter().map(|&id| graph.name(id).to_string()).collect()
+    }
+
+    #[test]
+    fn test_topological_sort() {
+        let graph = [
+            ("shirt", "tie"),
+            ("tie", "jacket"),
+            ("socks", "shoes"),
+            ("shirt", "belt"),
+            ("belt", "jacket"),
+            ("trousers", "shoes"),
+            ("trousers", "belt"),
+        ]
+        .into_iter()
+        .collect::<Graph>();
+        let order = graph.topological_sort().unwrap();
+        let position = |name: &str| order.iter().position(|&id| graph.name(id) == name);
+        assert_eq!(order.len(), 7);
+        assert!(position("shirt") < position("tie"));
+        assert!(position("belt") < position("jacket"));
+        assert!(position("trousers") < position("shoes"));
+
+        let mut graph = graph;
+        graph.add_edge("jacket", "trousers");
+        let cycle = graph.topological_sort().unwrap_err();
+        assert_eq!(names(&graph, &cycle), vec!["trousers", "belt", "jacket"]);
+    }
+
+    #[test]
+    fn test_self_loop() {
+        let graph = [("a", "b"), ("b", "b")].into_iter().collect::<Graph>();
+        assert_eq!(
+            names(&graph, &graph.topological_sort().unwrap_err()),
+            vec!["b"]
+        );
+    }
+
+    #[test]
+    fn test_strongly_connected_components() {
+        let graph = [
+            ("a", "b"),
+            ("b", "c"),
+            ("c", "a"),
+            ("c", "d"),
+            ("d", "e"),
+            ("e", "d"),
+            ("f", "e"),
+        ]
+        .into_iter()
+        .collect::<Graph>();
+        let mut components = graph
+            .strongly_connected_components()
+            .iter()
+            .map(|c| {
+                let mut c = names(&graph, c);
+                c.sort();
+                c
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(components[0], vec!["d", "e"]);
+        components.sort();
+        assert_eq!(
+            components,
+            vec![vec!["a", "b", "c"], vec!["d", "e"], vec!["f"]]
+        );
+
+        let path = (0..100_000)
+            .map(|i| (i.to_string(), (i + 1).to_string()))
+            .collect::<Graph>();
+        assert_eq!(path.strongly_connected_components().len(), 100_001);
+    }
+
+    #[test]
+    fn test_reachability() {
+        let graph = [("a", "b"), ("b", "c"), ("d", "a")]
+            .into_iter()
+            .collect::<Graph>();
+        let (a, d) = (graph.node("a").unwrap(), graph.node("d").unwrap());
+        assert_eq!(names(&graph, &graph.reachable_from(a)), vec!["a", "b", "c"]);
+        assert!(graph.is_reachable(d, graph.node("c").unwrap()));
+        assert!(!graph.is_reachable(a, d));
+    }
+
+    #[test]
+    fn test_to_dot() {
+        let mut graph = Graph::new();
+        graph.add_labelled_edge("in", "px", Some("s<1351".to_string()));
+        graph.add_edge("px", "say \"A\"");
+        graph.set_attributes(0, "shape=box");
+        assert_eq!(
+            graph.to_dot("workflows"),
+            "digraph \"workflows\" {
+    \"in\" [shape=box];
+    \"px\";
+    \"say \\\"A\\\"\";
+    \"in\" -> \"px\" [label=\"s<1351\"];
+    \"px\" -> \"say \\\"A\\\"\";
+}
+"
+        );
+    }
+}
@@ -0,0 +1,968 @@
+#[derive(Debug, PartialEq, Eq)]
+pub enum GridError {
+    RaggedRow {
+        row: usize,
+        expected: usize,
+        found: usize,
+    },
+    InvalidCharacter {
+        point: Point,
+        character: char,
+    },
+}
+
+impl std::fmt::Display for GridError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            GridError::RaggedRow {
+                row,
+                expected,
+                found,
+            } => write!(f, "Row {} has length {}, expected {}", row, found, expected),
+            GridError::InvalidCharacter { point, character } => write!(
+                f,
+                "Invalid character {:?} at ({}, {})",
+                character, point.0, point.1
+            ),
+        }
+    }
+}
+
+impl std::error::Error for GridError {}
+
+pub trait FromChar: Sized {
+    fn from_char(c: char) -> Option<Self>;
+}
+
+impl FromChar for char {
+    fn from_char(c: char) -> Option<Self> {
+        Some(c)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    pub fn new(blocks: Vec<Vec<T>>) -> Result<Self, GridError> {
+        let height = blocks.len();
+        let width = blocks.first().map_or(0, |row| row.len());
+        if let Some((row, found)) = blocks
+            .iter()
+            .map(|row| row.len())
+            .enumerate()
+            .find(|(_, len)| *len != width)
+        {
+            return Err(GridError::RaggedRow {
+                row,
+                expected: width,
+                found,
+            });
+        }
+
+        Ok(Self {
+            width,
+            height,
+            cells: blocks.into_iter().flatten().collect(),
+        })
+    }
+
+    pub fn filled(width: usize, height: usize, value: T) -> Self
+    where
+        T: Clone,
+    {
+        Self {
+            width,
+            height,
+            cells: vec![value; width * height],
+        }
+    }
+
+    /// Builds a grid from puzzle text, one row per line, mapping every
+    /// character through `f`.
+    pub fn parse_with<F>(input: &str, mut f: F) -> Result<Self, GridError>
+    where
+        F: FnMut(char) -> Option<T>,
+    {
+        let blocks = input
+            .lines()
+            .enumerate()
+            .map(|(y, line)| {
+                line.chars()
+                    .enumerate()
+                    .map(|(x, c)| {
+                        f(c).ok_or(GridError::InvalidCharacter {
+                            point: Point(x, y),
+                            character: c,
+                        })
+                    })
+                    .collect::<Result<Vec<_>, _>>()
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Self::new(blocks)
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn in_bounds(&self, point: Point) -> bool {
+        point.0 < self.width && point.1 < self.height
+    }
+
+    fn index(&self, point: Point) -> Option<usize> {
+        if self.in_bounds(point) {
+            Some(point.1 * self.width + point.0)
+        } else {
+            None
+        }
+    }
+
+    fn point(&self, index: usize) -> Point {
+        Point(index % self.width, index / self.width)
+    }
+
+    pub fn get(&self, point: Point) -> Option<&T> {
+        self.index(point).map(|index| &self.cells[index])
+    }
+
+    pub fn get_mut(&mut self, point: Point) -> Option<&mut T> {
+        self.index(point).map(|index| &mut self.cells[index])
+    }
+
+    /// Replaces the cell at `point`, returning the previous value, or `None`
+    /// if `point` is out of bounds.
+    pub fn set(&mut self, point: Point, value: T) -> Option<T> {
+        self.get_mut(point)
+            .map(|cell| std::mem::replace(cell, value))
+    }
+
+    pub fn row(&self, y: usize) -> Option<&[T]> {
+        if y < self.height {
+            Some(&self.cells[y * self.width..(y + 1) * self.width])
+        } else {
+            None
+        }
+    }
+
+    pub fn column(&self, x: usize) -> impl Iterator<Item = &T> {
+        let len = if x < self.width { self.height } else { 0 };
+        self.cells
+            .iter()
+            .skip(x)
+            .step_by(self.width.max(1))
+            .take(len)
+    }
+
+    /// Iterates over every cell in row-major order alongside its position.
+    pub fn iter_cells(&self) -> impl Iterator<Item = (Point, &T)> {
+        self.cells
+            .iter()
+            .enumerate()
+            .map(|(index, cell)| (self.point(index), cell))
+    }
+
+    /// Returns the first point, in row-major order, whose cell matches `predicate`.
+    pub fn find<P>(&self, mut predicate: P) -> Option<Point>
+    where
+        P: FnMut(&T) -> bool,
+    {
+        self.cells
+            .iter()
+            .position(&mut predicate)
+            .map(|index| self.point(index))
+    }
+
+    pub fn neighbors(
+        &self,
+        point: Point,
+        connectivity: Connectivity,
+    ) -> impl Iterator<Item = (Point, &T)> {
+        point
+            .neighbors(self, connectivity)
+            .map(|neighbor| (neighbor, &self[neighbor]))
+    }
+
+    /// Looks up `point` as if the grid were tiled infinitely in every direction.
+    pub fn get_wrapped(&self, point: SignedPoint) -> Option<&T> {
+        if self.width == 0 || self.height == 0 {
+            return None;
+        }
+
+        let x = point.0.rem_euclid(self.width as i64) as usize;
+        let y = point.1.rem_euclid(self.height as i64) as usize;
+        self.get(Point(x, y))
+    }
+
+    pub fn positions_of<'a>(&'a self, value: &'a T) -> impl Iterator<Item = Point> + 'a
+    where
+        T: PartialEq,
+    {
+        self.iter_cells()
+            .filter(move |(_, v)| *v == value)
+            .map(|(point, _)| point)
+    }
+}
+
+impl<T> std::ops::Index<Point> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, point: Point) -> &Self::Output {
+        match self.get(point) {
+            Some(cell) => cell,
+            None => panic!(
+                "Point ({}, {}) is outside of a {}x{} grid",
+                point.0, point.1, self.width, self.height
+            ),
+        }
+    }
+}
+
+impl<T> std::ops::IndexMut<Point> for Grid<T> {
+    fn index_mut(&mut self, point: Point) -> &mut Self::Output {
+        let (width, height) = (self.width, self.height);
+        match self.get_mut(point) {
+            Some(cell) => cell,
+            None => panic!(
+                "Point ({}, {}) is outside of a {}x{} grid",
+                point.0, point.1, width, height
+            ),
+        }
+    }
+}
+
+impl<T: FromChar> std::str::FromStr for Grid<T> {
+    type Err = GridError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse_with(s, T::from_char)
+    }
+}
+
+impl<T> Grid<T> {
+    pub fn view(&self) -> GridView<'_, T> {
+        GridView {
+            grid: self,
+            swap: false,
+            flip_x: false,
+            flip_y: false,
+        }
+    }
+}
+
+impl<T: Clone> Grid<T> {
+    pub fn transpose(&self) -> Self {
+        self.view().transpose().to_grid()
+    }
+
+    pub fn rotate_clockwise(&self) -> Self {
+        self.view().rotate_clockwise().to_grid()
+    }
+
+    pub fn rotate_counter_clockwise(&self) -> Self {
+        self.view().rotate_counter_clockwise().to_grid()
+    }
+
+    /// Mirrors the grid left to right.
+    pub fn flip_horizontal(&self) -> Self {
+        self.view().flip_horizontal().to_grid()
+    }
+
+    /// Mirrors the grid top to bottom.
+    pub fn flip_vertical(&self) -> Self {
+        self.view().flip_vertical().to_grid()
+    }
+}
+
+/// A transposed, rotated or mirrored window onto a `Grid` that reads through
+/// to the underlying cells instead of copying them.
+#[derive(Debug)]
+pub struct GridView<'a, T> {
+    grid: &'a Grid<T>,
+    swap: bool,
+    flip_x: bool,
+    flip_y: bool,
+}
+
+impl<T> Clone for GridView<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for GridView<'_, T> {}
+
+impl<'a, T> GridView<'a, T> {
+    pub fn width(&self) -> usize {
+        if self.swap {
+            self.grid.height
+        } else {
+            self.grid.width
+        }
+    }
+
+    pub fn height(&self) -> usize {
+        if self.swap {
+            self.grid.width
+        } else {
+            self.grid.height
+        }
+    }
+
+    pub fn in_bounds(&self, point: Point) -> bool {
+        point.0 < self.width() && point.1 < self.height()
+    }
+
+    fn source(&self, point: Point) -> Point {
+        let x = if self.flip_x {
+            self.width() - 1 - point.0
+        } else {
+            point.0
+        };
+        let y = if self.flip_y {
+            self.height() - 1 - point.1
+        } else {
+            point.1
+        };
+
+        if self.swap {
+            Point(y, x)
+        } else {
+            Point(x, y)
+        }
+    }
+
+    fn cell(&self, point: Point) -> &'a T {
+        let grid: &'a Grid<T> = self.grid;
+        &grid[self.source(point)]
+    }
+
+    pub fn get(&self, point: Point) -> Option<&'a T> {
+        if self.in_bounds(point) {
+            Some(self.cell(point))
+        } else {
+            None
+        }
+    }
+
+    pub fn transpose(self) -> Self {
+        Self {
+            swap: !self.swap,
+            flip_x: self.flip_y,
+            flip_y: self.flip_x,
+            ..self
+        }
+    }
+
+    pub fn flip_horizontal(self) -> Self {
+        Self {
+            flip_x: !self.flip_x,
+            ..self
+        }
+    }
+
+    pub fn flip_vertical(self) -> Self {
+        Self {
+            flip_y: !self.flip_y,
+            ..self
+        }
+    }
+
+    pub fn rotate_clockwise(self) -> Self {
+        self.transpose().flip_horizontal()
+    }
+
+    pub fn rotate_counter_clockwise(self) -> Self {
+        self.transpose().flip_vertical()
+    }
+
+    pub fn row(&self, y: usize) -> impl Iterator<Item = &'a T> {
+        let view = *self;
+        let len = if y < self.height() { self.width() } else { 0 };
+        (0..len).map(move |x| view.cell(Point(x, y)))
+    }
+
+    pub fn column(&self, x: usize) -> impl Iterator<Item = &'a T> {
+        let view = *self;
+        let len = if x < self.width() { self.height() } else { 0 };
+        (0..len).map(move |y| view.cell(Point(x, y)))
+    }
+
+    pub fn iter_cells(&self) -> impl Iterator<Item = (Point, &'a T)> {
+        let view = *self;
+        (0..self.height())
+            .flat_map(move |y| (0..view.width()).map(move |x| Point(x, y)))
+            .map(move |point| (point, view.cell(point)))
+    }
+
+    pub fn to_grid(&self) -> Grid<T>
+    where
+        T: Clone,
+    {
+        Grid {
+            width: self.width(),
+            height: self.height(),
+            cells: self.iter_cells().map(|(_, cell)| cell.clone()).collect(),
+        }
+    }
+}
+
+impl<T> std::ops::Index<Point> for GridView<'_, T> {
+    type Output = T;
+
+    fn index(&self, point: Point) -> &Self::Output {
+        match self.get(point) {
+            Some(cell) => cell,
+            None => panic!(
+                "Point ({}, {}) is outside of a {}x{} view",
+                point.0,
+                point.1,
+                self.width(),
+                self.height()
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, std::cmp::PartialOrd, std::cmp::Ord)]
+pub enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+    UpLeft,
+    UpRight,
+    DownLeft,
+    DownRight,
+}
+
+impl Direction {
+    pub const ORTHOGONAL: [Direction; 4] = [
+        Direction::Up,
+        Direction::Down,
+        Direction::Left,
+        Direction::Right,
+    ];
+
+    pub const ALL: [Direction; 8] = [
+        Direction::Up,
+        Direction::Down,
+        Direction::Left,
+        Direction::Right,
+        Direction::UpLeft,
+        Direction::UpRight,
+        Direction::DownLeft,
+        Direction::DownRight,
+    ];
+
+    pub fn all() -> impl Iterator<Item = Direction> {
+        Direction::ALL.into_iter()
+    }
+
+    pub fn orthogonal() -> impl Iterator<Item = Direction> {
+        Direction::ORTHOGONAL.into_iter()
+    }
+
+    /// Position in `ALL`, for tables indexed by direction. The orthogonal
+    /// directions come first, so they also index `ORTHOGONAL`.
+    pub fn index(&self) -> usize {
+        match self {
+            Direction::Up => 0,
+            Direction::Down => 1,
+            Direction::Left => 2,
+            Direction::Right => 3,
+            Direction::UpLeft => 4,
+            Direction::UpRight => 5,
+            Direction::DownLeft => 6,
+            Direction::DownRight => 7,
+        }
+    }
+
+    /// Rotates a quarter turn counter-clockwise.
+    pub fn turn_left(&self) -> Self {
+        match self {
+            Direction::Up => Direction::Left,
+            Direction::Left => Direction::Down,
+            Direction::Down => Direction::Right,
+            Direction::Right => Direction::Up,
+            Direction::UpLeft => Direction::DownLeft,
+            Direction::DownLeft => Direction::DownRight,
+            Direction::DownRight => Direction::UpRight,
+            Direction::UpRight => Direction::UpLeft,
+        }
+    }
+
+    /// Rotates a quarter turn clockwise.
+    pub fn turn_right(&self) -> Self {
+        self.opposite().turn_left()
+    }
+
+    pub fn opposite(&self) -> Self {
+        match self {
+            Direction::Up => Direction::Down,
+            Direction::Down => Direction::Up,
+            Direction::Left => Direction::Right,
+            Direction::Right => Direction::Left,
+            Direction::UpLeft => Direction::DownRight,
+            Direction::UpRight => Direction::DownLeft,
+            Direction::DownLeft => Direction::UpRight,
+            Direction::DownRight => Direction::UpLeft,
+        }
+    }
+
+    pub fn is_vertical(&self) -> bool {
+        matches!(self, Direction::Up | Direction::Down)
+    }
+
+    pub fn is_horizontal(&self) -> bool {
+        matches!(self, Direction::Left | Direction::Right)
+    }
+
+    /// The unit step taken when moving in this direction, with y growing downwards.
+    pub fn delta(&self) -> SignedPoint {
+        match self {
+            Direction::Up => SignedPoint(0, -1),
+            Direction::Down => SignedPoint(0, 1),
+            Direction::Left => SignedPoint(-1, 0),
+            Direction::Right => SignedPoint(1, 0),
+            Direction::UpLeft => SignedPoint(-1, -1),
+            Direction::UpRight => SignedPoint(1, -1),
+            Direction::DownLeft => SignedPoint(-1, 1),
+            Direction::DownRight => SignedPoint(1, 1),
+        }
+    }
+}
+
+/// Accepts the encodings used across the puzzles: `U/D/L/R`, `^v<>`,
+/// compass points `N/S/E/W`, and the digits `0-3` (right, down, left, up).
+impl FromChar for Direction {
+    fn from_char(c: char) -> Option<Self> {
+        match c {
+            'U' | '^' | 'N' | '3' => Some(Direction::Up),
+            'D' | 'v' | 'S' | '1' => Some(Direction::Down),
+            'L' | '<' | 'W' | '2' => Some(Direction::Left),
+            'R' | '>' | 'E' | '0' => Some(Direction::Right),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct ParseDirectionError;
+
+impl std::str::FromStr for Direction {
+    type Err = ParseDirectionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut chars = s.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => Direction::from_char(c).ok_or(ParseDirectionError),
+            _ => Err(ParseDirectionError),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Connectivity {
+    Four,
+    Eight,
+}
+
+impl Connectivity {
+    pub fn directions(&self) -> &'static [Direction] {
+        match self {
+            Connectivity::Four => &Direction::ORTHOGONAL,
+            Connectivity::Eight => &Direction::ALL,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, std::cmp::PartialOrd, std::cmp::Ord)]
+pub struct Point(pub usize, pub usize);
+
+impl Point {
+    pub fn new(x: usize, y: usize) -> Self {
+        Self(x, y)
+    }
+
+    pub fn move_direction(&self, dir: Direction) -> Option<Self> {
+        let pos = match dir {
+            Direction::Up => Point(self.0, self.1.checked_sub(1)?),
+            Direction::Down => Point(self.0, self.1 + 1),
+            Direction::Left => Point(self.0.checked_sub(1)?, self.1),
+            Direction::Right => Point(self.0 + 1, self.1),
+            Direction::UpLeft => Point(self.0.checked_sub(1)?, self.1.checked_sub(1)?),
+            Direction::UpRight => Point(self.0 + 1, self.1.checked_sub(1)?),
+            Direction::DownLeft => Point(self.0.checked_sub(1)?, self.1 + 1),
+            Direction::DownRight => Point(self.0 + 1, self.1 + 1),
+        };
+
+        Some(pos)
+    }
+
+    pub fn neighbors<'a, T>(
+        &self,
+        grid: &'a Grid<T>,
+        connectivity: Connectivity,
+    ) -> impl Iterator<Item = Point> + 'a {
+        let point = *self;
+        connectivity
+            .directions()
+            .iter()
+            .filter_map(move |dir| point.move_direction(*dir))
+            .filter(|neighbor| grid.in_bounds(*neighbor))
+    }
+
+    pub fn neighbors4<'a, T>(&self, grid: &'a Grid<T>) -> impl Iterator<Item = Point> + 'a {
+        self.neighbors(grid, Connectivity::Four)
+    }
+
+    pub fn neighbors8<'a, T>(&self, grid: &'a Grid<T>) -> impl Iterator<Item = Point> + 'a {
+        self.neighbors(grid, Connectivity::Eight)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, std::cmp::PartialOrd, std::cmp::Ord, Default)]
+pub struct SignedPoint(pub i64, pub i64);
+
+impl SignedPoint {
+    pub fn new(x: i64, y: i64) -> Self {
+        Self(x, y)
+    }
+
+    pub fn move_direction(&self, dir: Direction) -> Self {
+        *self + SignedPoint::from(dir)
+    }
+
+    /// In `u128`, as the distance between far corners doesn't fit in `u64`.
+    pub fn manhattan_distance(&self, other: SignedPoint) -> u128 {
+        self.0.abs_diff(other.0) as u128 + self.1.abs_diff(other.1) as u128
+    }
+
+    /// Converts back to a `Point` if this lies inside `grid`.
+    pub fn to_point<T>(&self, grid: &Grid<T>) -> Option<Point> {
+        Point::try_from(*self)
+            .ok()
+            .filter(|point| grid.in_bounds(*point))
+    }
+}
+
+impl From<Direction> for SignedPoint {
+    fn from(dir: Direction) -> Self {
+        dir.delta()
+    }
+}
+
+impl From<Point> for SignedPoint {
+    fn from(point: Point) -> Self {
+        SignedPoint(point.0 as i64, point.1 as i64)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct NegativeCoordinateError;
+
+impl TryFrom<SignedPoint> for Point {
+    type Error = NegativeCoordinateError;
+
+    fn try_from(point: SignedPoint) -> Result<Self, Self::Error> {
+        match (usize::try_from(point.0), usize::try_from(point.1)) {
+            (Ok(x), Ok(y)) => Ok(Point(x, y)),
+            _ => Err(NegativeCoordinateError),
+        }
+    }
+}
+
+impl std::ops::Add for SignedPoint {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self::Output {
+        SignedPoint(self.0 + other.0, self.1 + other.1)
+    }
+}
+
+impl std::ops::AddAssign for SignedPoint {
+    fn add_assign(&mut self, other: Self) {
+        *self = *self + other;
+    }
+}
+
+impl std::ops::Sub for SignedPoint {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self::Output {
+        SignedPoint(self.0 - other.0, self.1 - other.1)
+    }
+}
+
+impl std::ops::SubAssign for SignedPoint {
+    fn sub_assign(&mut self, other: Self) {
+        *self = *self - other;
+    }
+}
+
+impl std::ops::Mul<i64> for SignedPoint {
+    type Output = Self;
+
+    fn mul(self, scale: i64) -> Self::Output {
+        SignedPoint(self.0 * scale, self.1 * scale)
+    }
+}
+
+impl std::ops::Neg for SignedPoint {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        SignedPoint(-self.0, -self.1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rectangular_grid() {
+        let grid = Grid::new(vec![vec![1, 2, 3], vec![4, 5, 6]]).unwrap();
+        assert_eq!(grid.width(), 3);
+        assert_eq!(grid.height(), 2);
+        assert!(grid.in_bounds(Point(2, 1)));
+        assert!(!grid.in_bounds(Point(1, 2)));
+        assert!(!grid.in_bounds(Point(3, 0)));
+        assert_eq!(grid.get(Point(2, 1)), Some(&6));
+        assert_eq!(grid.get(Point(1, 2)), None);
+    }
+
+    #[test]
+    fn test_filled() {
+        let grid = Grid::filled(4, 2, '.');
+        assert_eq!(grid.width(), 4);
+        assert_eq!(grid.height(), 2);
+        assert!(grid.iter_cells().all(|(_, c)| *c == '.'));
+    }
+
+    #[test]
+    fn test_parse() {
+        let grid = Grid::parse_with("123\n456\n", |c| c.to_digit(10)).unwrap();
+        assert_eq!(grid.width(), 3);
+        assert_eq!(grid.height(), 2);
+        assert_eq!(grid.get(Point(1, 1)), Some(&5));
+
+        let grid = Grid::parse_with("123\n4x6", |c| c.to_digit(10));
+        assert_eq!(
+            grid.unwrap_err(),
+            GridError::InvalidCharacter {
+                point: Point(1, 1),
+                character: 'x'
+            }
+        );
+    }
+
+    #[test]
+    fn test_find() {
+        let grid = "..#.\n.S..\n#..#".parse::<Grid<char>>().unwrap();
+        assert_eq!(grid.find(|c| *c == 'S'), Some(Point(1, 1)));
+        assert_eq!(grid.find(|c| *c == 'x'), None);
+        assert_eq!(
+            grid.positions_of(&'#').collect::<Vec<_>>(),
+            vec![Point(2, 0), Point(0, 2), Point(3, 2)]
+        );
+    }
+
+    #[test]
+    fn test_rows_and_columns() {
+        let mut grid = Grid::new(vec![vec![1, 2, 3], vec![4, 5, 6]]).unwrap();
+        assert_eq!(grid.row(1), Some(&[4, 5, 6][..]));
+        assert_eq!(grid.row(2), None);
+        assert_eq!(grid.column(2).collect::<Vec<_>>(), vec![&3, &6]);
+        assert_eq!(grid.column(3).count(), 0);
+
+        assert_eq!(grid.set(Point(0, 1), 7), Some(4));
+        assert_eq!(grid.set(Point(0, 2), 7), None);
+        *grid.get_mut(Point(2, 0)).unwrap() += 10;
+        grid[Point(1, 1)] = 0;
+        assert_eq!(grid[Point(2, 0)], 13);
+        assert_eq!(
+            grid.iter_cells().collect::<Vec<_>>(),
+            vec![
+                (Point(0, 0), &1),
+                (Point(1, 0), &2),
+                (Point(2, 0), &13),
+                (Point(0, 1), &7),
+                (Point(1, 1), &0),
+                (Point(2, 1), &6),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_neighbors() {
+        let grid = Grid::new(vec![vec![1, 2, 3], vec![4, 5, 6]]).unwrap();
+        assert_eq!(
+            Point(0, 0).neighbors4(&grid).collect::<Vec<_>>(),
+            vec![Point(0, 1), Point(1, 0)]
+        );
+        assert_eq!(
+            Point(0, 0).neighbors8(&grid).collect::<Vec<_>>(),
+            vec![Point(0, 1), Point(1, 0), Point(1, 1)]
+        );
+        assert_eq!(Point(1, 1).neighbors8(&grid).count(), 5);
+        assert_eq!(
+            grid.neighbors(Point(2, 1), Connectivity::Eight)
+                .map(|(_, v)| *v)
+                .collect::<Vec<_>>(),
+            vec![3, 5, 2]
+        );
+    }
+
+    #[test]
+    fn test_direction_algebra() {
+        Direction::all().for_each(|dir| {
+            assert_eq!(dir.turn_left().turn_right(), dir);
+            assert_eq!(dir.turn_left().turn_left(), dir.opposite());
+            assert_eq!(dir.opposite().delta(), -dir.delta());
+            assert_eq!(
+                dir.turn_right().delta(),
+                SignedPoint(-dir.delta().1, dir.delta().0)
+            );
+        });
+        assert_eq!(Direction::Up.turn_right(), Direction::Right);
+        assert_eq!(Direction::UpLeft.turn_right(), Direction::UpRight);
+        assert_eq!(Direction::orthogonal().count(), 4);
+        assert!(Direction::orthogonal().all(|dir| dir.is_vertical() ^ dir.is_horizontal()));
+        assert!(Direction::all()
+            .enumerate()
+            .all(|(i, dir)| dir.index() == i));
+    }
+
+    #[test]
+    fn test_parse_direction() {
+        assert_eq!(
+            "URDL".chars().map(Direction::from_char).collect::<Vec<_>>(),
+            "^>v<".chars().map(Direction::from_char).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            "NESW"
+                .chars()
+                .filter_map(Direction::from_char)
+                .collect::<Vec<_>>(),
+            vec![
+                Direction::Up,
+                Direction::Right,
+                Direction::Down,
+                Direction::Left
+            ]
+        );
+        assert_eq!(
+            "0123"
+                .chars()
+                .filter_map(Direction::from_char)
+                .collect::<Vec<_>>(),
+            vec![
+                Direction::Right,
+                Direction::Down,
+                Direction::Left,
+                Direction::Up
+            ]
+        );
+        assert_eq!("v".parse::<Direction>(), Ok(Direction::Down));
+        assert_eq!("UD".parse::<Direction>(), Err(ParseDirectionError));
+        assert_eq!("x".parse::<Direction>(), Err(ParseDirectionError));
+    }
+
+    #[test]
+    fn test_signed_point() {
+        let grid = Grid::new(vec![vec![1, 2, 3], vec![4, 5, 6]]).unwrap();
+        let p = SignedPoint(2, 1) + SignedPoint::from(Direction::Up) * 3;
+        assert_eq!(p, SignedPoint(2, -2));
+        assert_eq!(-p, SignedPoint(-2, 2));
+        assert_eq!(p - SignedPoint(4, 4), SignedPoint(-2, -6));
+        assert_eq!(p.manhattan_distance(SignedPoint(-1, 2)), 7);
+        assert_eq!(
+            SignedPoint(i64::MIN, i64::MIN).manhattan_distance(SignedPoint(i64::MAX, i64::MAX)),
+            2 * u64::MAX as u128
+        );
+        assert_eq!(p.to_point(&grid), None);
+        assert_eq!(Point::try_from(p), Err(NegativeCoordinateError));
+        assert_eq!(SignedPoint(2, 1).to_point(&grid), Some(Point(2, 1)));
+        assert_eq!(SignedPoint(3, 1).to_point(&grid), None);
+        assert_eq!(SignedPoint::from(Point(4, 7)), SignedPoint(4, 7));
+    }
+
+    #[test]
+    fn test_get_wrapped() {
+        let grid = Grid::new(vec![vec![1, 2, 3], vec![4, 5, 6]]).unwrap();
+        assert_eq!(grid.get_wrapped(SignedPoint(0, 0)), Some(&1));
+        assert_eq!(grid.get_wrapped(SignedPoint(-1, 0)), Some(&3));
+        assert_eq!(grid.get_wrapped(SignedPoint(4, -1)), Some(&5));
+        assert_eq!(grid.get_wrapped(SignedPoint(-7, 9)), Some(&6));
+    }
+
+    #[test]
+    fn test_transformations() {
+        let grid = Grid::new(vec![vec![1, 2, 3], vec![4, 5, 6]]).unwrap();
+        let rows = |grid: Grid<i32>| {
+            (0..grid.height())
+                .map(|y| grid.row(y).unwrap().to_vec())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(rows(grid.transpose()), vec![[1, 4], [2, 5], [3, 6]]);
+        assert_eq!(rows(grid.rotate_clockwise()), vec![[4, 1], [5, 2], [6, 3]]);
+        assert_eq!(
+            rows(grid.rotate_counter_clockwise()),
+            vec![[3, 6], [2, 5], [1, 4]]
+        );
+        assert_eq!(rows(grid.flip_horizontal()), vec![[3, 2, 1], [6, 5, 4]]);
+        assert_eq!(rows(grid.flip_vertical()), vec![[4, 5, 6], [1, 2, 3]]);
+        assert_eq!(
+            grid.rotate_clockwise().rotate_counter_clockwise(),
+            grid.clone()
+        );
+        assert_eq!(
+            grid.rotate_clockwise().rotate_clockwise(),
+            grid.flip_horizontal().flip_vertical()
+        );
+    }
+
+    #[test]
+    fn test_view() {
+        let grid = Grid::new(vec![vec![1, 2, 3], vec![4, 5, 6]]).unwrap();
+        let view = grid.view().rotate_clockwise().flip_vertical();
+        assert_eq!(view.width(), 2);
+        assert_eq!(view.height(), 3);
+        assert_eq!(view[Point(0, 0)], 6);
+        assert_eq!(view.get(Point(2, 0)), None);
+        assert_eq!(view.row(1).collect::<Vec<_>>(), vec![&5, &2]);
+        assert_eq!(view.column(1).collect::<Vec<_>>(), vec![&3, &2, &1]);
+        assert_eq!(
+            view.transpose().to_grid(),
+            grid.flip_horizontal().flip_vertical()
+        );
+        (0..4)
+            .fold(grid.view(), |view, _| view.rotate_counter_clockwise())
+            .iter_cells()
+            .for_each(|(point, cell)| assert_eq!(&grid[point], cell));
+    }
+
+    #[test]
+    fn test_ragged_rows() {
+        let grid = Grid::new(vec![vec![1, 2, 3], vec![4, 5]]);
+        assert_eq!(
+            grid.unwrap_err(),
+            GridError::RaggedRow {
+                row: 1,
+                expected: 3,
+                found: 2
+            }
+        );
+    }
+}
@@ -0,0 +1,251 @@
+use crate::interval::Interval;
+use std::rc::Rc;
+
+/// An axis-aligned box with one half-open `Interval` per named dimension.
+/// Boxes built from the same names can be intersected and subtracted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HyperRect {
+    names: Rc<[String]>,
+    ranges: Vec<Interval>,
+}
+
+impl HyperRect {
+    pub fn new(dimensions: &[(&str, Interval)]) -> Self {
+        Self {
+            names: dimensions
+                .iter()
+                .map(|(name, _)| name.to_string())
+                .collect(),
+            ranges: dimensions.iter().map(|(_, range)| *range).collect(),
+        }
+    }
+
+    /// A box spanning `range` in every dimension.
+    pub fn uniform(names: &[&str], range: Interval) -> Self {
+        Self {
+            names: names.iter().map(|name| name.to_string()).collect(),
+            ranges: vec![range; names.len()],
+        }
+    }
+
+    pub fn dimensions(&self) -> usize {
+        self.ranges.len()
+    }
+
+    pub fn names(&self) -> &[String] {
+        &self.names
+    }
+
+    pub fn index_of(&self, name: &str) -> Option<usize> {
+        self.names.iter().position(|n| n == name)
+    }
+
+    pub fn range(&self, dimension: usize) -> Interval {
+        self.ranges[dimension]
+    }
+
+    pub fn get(&self, name: &str) -> Option<Interval> {
+        self.index_of(name).map(|i| self.ranges[i])
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.iter().any(Interval::is_empty)
+    }
+
+    /// Number of integer points inside. Panics if it doesn't fit in `u128`.
+    pub fn volume(&self) -> u128 {
+        self.ranges.iter().map(|r| r.len() as u128).product()
+    }
+
+    pub fn contains(&self, point: &[i64]) -> bool {
+        point.len() == self.dimensions()
+            && self.ranges.iter().zip(point).all(|(r, v)| r.contains(*v))
+    }
+
+    fn with_range(&self, dimension: usize, range: Interval) -> Self {
+        let mut res = self.clone();
+        res.ranges[dimension] = range;
+        res
+    }
+
+    /// Cuts the box along `dimension` into the points below `threshold` and
+    /// the points at or above it. Empty halves are `None`.
+    pub fn split(&self, dimension: usize, threshold: i64) -> (Option<Self>, Option<Self>) {
+        let range = self.ranges[dimension];
+        let threshold = threshold.clamp(range.start, range.end.max(range.start));
+        let below = self.with_range(dimension, Interval::new(range.start, threshold));
+        let above = self.with_range(dimension, Interval::new(threshold, range.end));
+        (
+            Some(below).filter(|r| !r.is_empty()),
+            Some(above).filter(|r| !r.is_empty()),
+        )
+    }
+
+    fn assert_compatible(&self, other: &Self) {
+        assert!(
+            self.names == other.names,
+            "Mismatched dimensions {:?} and {:?}",
+            self.names,
+            other.names
+        );
+    }
+
+    pub fn intersection(&self, other: &Self) -> Option<Self> {
+        self.assert_compatible(other);
+        let ranges = self
+            .ranges
+            .iter()
+            .zip(&other.ranges)
+            .map(|(a, b)| a.intersection(b))
+            .collect();
+        Some(Self {
+            names: self.names.clone(),
+            ranges,
+        })
+        .filter(|r| !r.is_empty())
+    }
+
+    /// Disjoint boxes covering the points of `self` outside `other`, at most
+    /// two per dimension.
+    pub fn difference(&self, other: &Self) -> Vec<Self> {
+        let Some(overlap) = self.intersection(other) else {
+            return match self.is_empty() {
+                true => vec![],
+                false => vec![self.clone()],
+            };
+        };
+
+        // Peel off the slabs below and above the overlap one dimension at a
+        // time, keeping the core that still contains it.
+        let mut pieces = vec![];
+        let mut core = self.clone();
+        for (dimension, range) in overlap.ranges.iter().enumerate() {
+            let (below, rest) = core.split(dimension, range.start);
+            let (middle, above) = rest
+                .expect("Overlap lies inside the box")
+                .split(dimension, range.end);
+            pieces.extend(below);
+            pieces.extend(above);
+            core = middle.expect("Overlap lies inside the box");
+        }
+
+        pieces
+    }
+}
+
+/// A union of boxes kept as disjoint pieces, so its volume is the sum of
+/// theirs.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DisjointUnion {
+    rects: Vec<HyperRect>,
+}
+
+impl DisjointUnion {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn rects(&self) -> &[HyperRect] {
+        &self.rects
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rects.is_empty()
+    }
+
+    /// Adds the parts of `rect` not already covered.
+    pub fn insert(&mut self, rect: HyperRect) {
+        let new_pieces = self.rects.iter().fold(vec![rect], |pieces, existing| {
+            pieces
+                .iter()
+                .flat_map(|piece| piece.difference(existing))
+                .collect()
+        });
+        self.rects
+            .extend(new_pieces.into_iter().filter(|r| !r.is_empty()));
+    }
+
+    pub fn volume(&self) -> u128 {
+        self.rects.iter().map(HyperRect::volume).sum()
+    }
+
+    pub fn contains(&self, point: &[i64]) -> bool {
+        self.rects.iter().any(|r| r.contains(point))
+    }
+}
+
+impl FromIterator<HyperRect> for DisjointUnion {
+    fn from_iter<I: IntoIterator<Item = HyperRect>>(iter: I) -> Self {
+        let mut union = Self::new();
+        iter.into_iter().for_each(|r| union.insert(r));
+        union
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rect(x: (i64, i64), y: (i64, i64)) -> HyperRect {
+        HyperRect::new(&[
+            ("x", Interval::new(x.0, x.1)),
+            ("y", Interval::new(y.0, y.1)),
+        ])
+    }
+
+    #[test]
+    fn test_split() {
+        let r = HyperRect::uniform(&["x", "m", "a", "s"], Interval::new(1, 4001));
+        assert_eq!(r.volume(), 4000u128.pow(4));
+        assert_eq!(r.index_of("a"), Some(2));
+
+        let (below, above) = r.split(2, 2006);
+        let (below, above) = (below.unwrap(), above.unwrap());
+        assert_eq!(below.get("a"), Some(Interval::new(1, 2006)));
+        assert_eq!(above.get("a"), Some(Interval::new(2006, 4001)));
+        assert_eq!(below.volume() + above.volume(), r.volume());
+
+        assert_eq!(r.split(0, 1), (None, Some(r.clone())));
+        assert_eq!(r.split(0, 5000), (Some(r.clone()), None));
+    }
+
+    #[test]
+    fn test_intersection_and_difference() {
+        let a = rect((0, 10), (0, 10));
+        let b = rect((5, 15), (-5, 5));
+        assert_eq!(a.intersection(&b), Some(rect((5, 10), (0, 5))));
+        assert_eq!(a.intersection(&rect((10, 20), (0, 10))), None);
+
+        let pieces = a.difference(&b);
+        assert_eq!(pieces.iter().map(HyperRect::volume).sum::<u128>(), 75);
+        assert!(pieces.iter().all(|p| p.intersection(&b).is_none()));
+        assert_eq!(a.difference(&a), vec![]);
+        assert_eq!(a.difference(&rect((20, 30), (0, 1))), vec![a.clone()]);
+    }
+
+    #[test]
+    fn test_disjoint_union() {
+        let union = [
+            rect((0, 10), (0, 10)),
+            rect((5, 15), (5, 15)),
+            rect((2, 3), (2, 3)),
+        ]
+        .into_iter()
+        .collect::<DisjointUnion>();
+        assert_eq!(union.volume(), 100 + 100 - 25);
+        assert!(union.contains(&[14, 14]));
+        assert!(!union.contains(&[14, 2]));
+        assert!(union.rects().iter().enumerate().all(|(i, a)| {
+            union.rects()[i + 1..]
+                .iter()
+                .all(|b| a.intersection(b).is_none())
+        }));
+    }
+
+    #[test]
+    #[should_panic(expected = "Mismatched dimensions")]
+    fn test_mismatched_dimensions() {
+        let a = HyperRect::uniform(&["x"], Interval::new(0, 1));
+        a.intersection(&rect((0, 1), (0, 1)));
+    }
+}
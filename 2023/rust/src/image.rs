@@ -0,0 +1,244 @@
+use crate::grid::{Grid, Point};
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rgb(pub u8, pub u8, pub u8);
+
+impl Rgb {
+    pub const BLACK: Rgb = Rgb(0, 0, 0);
+    pub const WHITE: Rgb = Rgb(255, 255, 255);
+
+    /// Rec. 601 luma, used when writing greyscale images.
+    pub fn luma(&self) -> u8 {
+        ((299 * self.0 as u32 + 587 * self.1 as u32 + 114 * self.2 as u32) / 1000) as u8
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Image {
+    width: usize,
+    height: usize,
+    pixels: Vec<Rgb>,
+}
+
+impl Image {
+    pub fn new(width: usize, height: usize, fill: Rgb) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![fill; width * height],
+        }
+    }
+
+    /// Paints every cell of `grid` as a `scale` x `scale` block of pixels.
+    pub fn from_grid<T, F>(grid: &Grid<T>, scale: usize, color: F) -> Self
+    where
+        F: Fn(Point, &T) -> Rgb,
+    {
+        let scale = scale.max(1);
+        let mut image = Self::new(grid.width() * scale, grid.height() * scale, Rgb::BLACK);
+        grid.iter_cells().for_each(|(point, cell)| {
+            let color = color(point, cell);
+            for y in point.1 * scale..(point.1 + 1) * scale {
+                let row = y * image.width;
+                image.pixels[row + point.0 * scale..row + (point.0 + 1) * scale].fill(color);
+            }
+        });
+        image
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn pixel(&self, x: usize, y: usize) -> Option<Rgb> {
+        (x < self.width && y < self.height).then(|| self.pixels[y * self.width + x])
+    }
+
+    pub fn pixels(&self) -> &[Rgb] {
+        &self.pixels
+    }
+
+    /// Binary PPM (`P6`).
+    pub fn to_ppm(&self) -> Vec<u8> {
+        let mut res = format!("P6\n{} {}\n255\n", self.width, self.height).into_bytes();
+        res.extend(self.pixels.iter().flat_map(|p| [p.0, p.1, p.2]));
+        res
+    }
+
+    /// Binary PGM (`P5`), colours are reduced to their luma.
+    pub fn to_pgm(&self) -> Vec<u8> {
+        let mut res = format!("P5\n{} {}\n255\n", self.width, self.height).into_bytes();
+        res.extend(self.pixels.iter().map(Rgb::luma));
+        res
+    }
+
+    /// 8-bit truecolour PNG. The image data is stored uncompressed inside the
+    /// zlib stream, which keeps the encoder tiny at the cost of file size.
+    pub fn to_png(&self) -> Vec<u8> {
+        let mut res = b"\x89PNG\r\n\x1a\n".to_vec();
+
+        let mut header = vec![];
+        header.extend((self.width as u32).to_be_bytes());
+        header.extend((self.height as u32).to_be_bytes());
+        header.extend([8, 2, 0, 0, 0]);
+        png_chunk(&mut res, b"IHDR", &header);
+
+        let mut raw = Vec::with_capacity((self.width * 3 + 1) * self.height);
+        (0..self.height).for_each(|y| {
+            raw.push(0);
+            raw.extend(
+                self.pixels[y * self.width..(y + 1) * self.width]
+                    .iter()
+                    .flat_map(|p| [p.0, p.1, p.2]),
+            );
+        });
+        png_chunk(&mut res, b"IDAT", &zlib_stored(&raw));
+        png_chunk(&mut res, b"IEND", &[]);
+        res
+    }
+
+    /// Writes the image, picking the format from the file extension.
+    pub fn save(&self, path: &Path) -> std::io::Result<()> {
+        let bytes = match path.extension().and_then(|e| e.to_str()) {
+            Some("ppm") => self.to_ppm(),
+            Some("pgm") => self.to_pgm(),
+            Some("png") => self.to_png(),
+            _ => {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    "Unsupported image format, expected .ppm, .pgm or .png",
+                ))
+            }
+        };
+        std::fs::write(path, bytes)
+    }
+}
+
+fn png_chunk(out: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    out.extend((data.len() as u32).to_be_bytes());
+    let start = out.len();
+    out.extend(kind);
+    out.extend(data);
+    let crc = crc32(&out[start..]);
+    out.extend(crc.to_be_bytes());
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let table = (0..256u32)
+        .map(|n| {
+            (0..8).fold(n, |c, _| {
+                if c & 1 == 1 {
+                    0xedb88320 ^ (c >> 1)
+                } else {
+                    c >> 1
+                }
+            })
+        })
+        .collect::<Vec<_>>();
+    !data.iter().fold(!0u32, |crc, &byte| {
+        table[((crc ^ byte as u32) & 0xff) as usize] ^ (crc >> 8)
+    })
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    let (a, b) = data.iter().fold((1u32, 0u32), |(a, b), &byte| {
+        let a = (a + byte as u32) % 65521;
+        (a, (b + a) % 65521)
+    });
+    (b << 16) | a
+}
+
+/// Wraps `data` in a zlib stream made of uncompressed deflate blocks.
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    let mut res = vec![0x78, 0x01];
+    let mut blocks = data.chunks(u16::MAX as usize).peekable();
+    if blocks.peek().is_none() {
+        res.extend([1, 0, 0, 0xff, 0xff]);
+    }
+    while let Some(block) = blocks.next() {
+        res.push(blocks.peek().is_none() as u8);
+        res.extend((block.len() as u16).to_le_bytes());
+        res.extend((!(block.len() as u16)).to_le_bytes());
+        res.extend(block);
+    }
+    res.extend(adler32(data).to_be_bytes());
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Image {
+        let grid = "#.\n.#".parse::<Grid<char>>().unwrap();
+        Image::from_grid(&grid, 2, |_, c| match c {
+            '#' => Rgb(255, 0, 0),
+            _ => Rgb::WHITE,
+        })
+    }
+
+    #[test]
+    fn test_from_grid() {
+        let image = sample();
+        assert_eq!((image.width(), image.height()), (4, 4));
+        assert_eq!(image.pixel(1, 1), Some(Rgb(255, 0, 0)));
+        assert_eq!(image.pixel(2, 1), Some(Rgb::WHITE));
+        assert_eq!(image.pixel(3, 3), Some(Rgb(255, 0, 0)));
+        assert_eq!(image.pixel(4, 0), None);
+    }
+
+    #[test]
+    fn test_netpbm() {
+        let image = sample();
+        let ppm = image.to_ppm();
+        assert!(ppm.starts_with(b"P6\n4 4\n255\n"));
+        assert_eq!(ppm.len(), 11 + 4 * 4 * 3);
+        assert_eq!(&ppm[11..17], &[255, 0, 0, 255, 0, 0]);
+
+        let pgm = image.to_pgm();
+        assert!(pgm.starts_with(b"P5\n4 4\n255\n"));
+        assert_eq!(&pgm[11..15], &[76, 76, 255, 255]);
+    }
+
+    #[test]
+    fn test_checksums() {
+        assert_eq!(crc32(b"IEND"), 0xae426082);
+        assert_eq!(crc32(b"123456789"), 0xcbf43926);
+        assert_eq!(adler32(b"Wikipedia"), 0x11e60398);
+    }
+
+    #[test]
+    fn test_png() {
+        let png = sample().to_png();
+        assert!(png.starts_with(b"\x89PNG\r\n\x1a\n"));
+        assert!(png.ends_with(&[0, 0, 0, 0, b'I', b'E', b'N', b'D', 0xae, 0x42, 0x60, 0x82]));
+        assert_eq!(&png[12..16], b"IHDR");
+        assert_eq!(&png[16..24], &[0, 0, 0, 4, 0, 0, 0, 4]);
+
+        let len = u32::from_be_bytes(png[33..37].try_into().unwrap()) as usize;
+        assert_eq!(&png[37..41], b"IDAT");
+        let idat = &png[41..41 + len];
+        let raw = &idat[7..idat.len() - 4];
+        assert_eq!(raw.len(), 4 * (1 + 4 * 3));
+        assert_eq!(&raw[..7], &[0, 255, 0, 0, 255, 0, 0]);
+        assert_eq!(idat[idat.len() - 4..], adler32(raw).to_be_bytes());
+    }
+
+    #[test]
+    fn test_zlib_blocks() {
+        let data = vec![7u8; 70_000];
+        let stream = zlib_stored(&data);
+        assert_eq!(stream.len(), 2 + 5 + 65535 + 5 + 4465 + 4);
+        assert_eq!(stream[2], 0);
+        assert_eq!(stream[2 + 5 + 65535], 1);
+        assert_eq!(
+            zlib_stored(&[]),
+            vec![0x78, 0x01, 1, 0, 0, 0xff, 0xff, 0, 0, 0, 1]
+        );
+    }
+}
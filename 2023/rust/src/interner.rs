@@ -0,0 +1,73 @@
+use std::collections::HashMap;
+
+/// Hands out dense ids `0, 1, 2, ...` for strings in the order they are
+/// first seen, and remembers each string for its id.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Interner {
+    ids: HashMap<String, usize>,
+    names: Vec<String>,
+}
+
+impl Interner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Id for `name`, assigning the next free one if it is new.
+    pub fn intern(&mut self, name: &str) -> usize {
+        if let Some(&id) = self.ids.get(name) {
+            return id;
+        }
+
+        let id = self.names.len();
+        self.ids.insert(name.to_string(), id);
+        self.names.push(name.to_string());
+        id
+    }
+
+    pub fn get(&self, name: &str) -> Option<usize> {
+        self.ids.get(name).copied()
+    }
+
+    pub fn name(&self, id: usize) -> Option<&str> {
+        self.names.get(id).map(String::as_str)
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    /// Names with their ids, in id order.
+    pub fn iter(&self) -> impl Iterator<Item = (usize, &str)> {
+        self.names.iter().map(String::as_str).enumerate()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_intern() {
+        let mut interner = Interner::new();
+        assert!(interner.is_empty());
+        assert_eq!(interner.intern("11A"), 0);
+        assert_eq!(interner.intern("ZZZ"), 1);
+        assert_eq!(interner.intern("11A"), 0);
+        assert_eq!(interner.intern(""), 2);
+        assert_eq!(interner.len(), 3);
+
+        assert_eq!(interner.get("ZZZ"), Some(1));
+        assert_eq!(interner.get("zzz"), None);
+        assert_eq!(interner.name(0), Some("11A"));
+        assert_eq!(interner.name(3), None);
+        assert_eq!(
+            interner.iter().collect::<Vec<_>>(),
+            vec![(0, "11A"), (1, "ZZZ"), (2, "")]
+        );
+    }
+}
@@ -0,0 +1,246 @@
+use std::cmp::Ordering;
+
+/// Half-open range `start..end` of integers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Interval {
+    pub start: i64,
+    pub end: i64,
+}
+
+impl Interval {
+    pub fn new(start: i64, end: i64) -> Self {
+        Self { start, end }
+    }
+
+    /// The `length` integers starting at `start`.
+    pub fn with_length(start: i64, length: i64) -> Self {
+        Self::new(start, start + length)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.start >= self.end
+    }
+
+    pub fn len(&self) -> u64 {
+        match self.is_empty() {
+            true => 0,
+            false => self.start.abs_diff(self.end),
+        }
+    }
+
+    pub fn contains(&self, value: i64) -> bool {
+        self.start <= value && value < self.end
+    }
+
+    pub fn intersection(&self, other: &Interval) -> Interval {
+        Interval::new(self.start.max(other.start), self.end.min(other.end))
+    }
+
+    pub fn shift(&self, offset: i64) -> Interval {
+        Interval::new(self.start + offset, self.end + offset)
+    }
+}
+
+/// A set of integers stored as sorted, disjoint, non-adjacent intervals.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct IntervalSet {
+    intervals: Vec<Interval>,
+}
+
+impl IntervalSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn intervals(&self) -> &[Interval] {
+        &self.intervals
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.intervals.is_empty()
+    }
+
+    /// Number of integers in the set.
+    pub fn len(&self) -> u128 {
+        self.intervals.iter().map(|i| i.len() as u128).sum()
+    }
+
+    pub fn min(&self) -> Option<i64> {
+        self.intervals.first().map(|i| i.start)
+    }
+
+    pub fn max(&self) -> Option<i64> {
+        self.intervals.last().map(|i| i.end - 1)
+    }
+
+    pub fn contains(&self, value: i64) -> bool {
+        self.intervals
+            .binary_search_by(|i| match i.contains(value) {
+                true => Ordering::Equal,
+                false if i.end <= value => Ordering::Less,
+                false => Ordering::Greater,
+            })
+            .is_ok()
+    }
+
+    pub fn insert(&mut self, interval: Interval) {
+        if interval.is_empty() {
+            return;
+        }
+
+        // Everything touching `interval` sits between these two indices and
+        // collapses into a single interval.
+        let first = self.intervals.partition_point(|i| i.end < interval.start);
+        let last = self.intervals.partition_point(|i| i.start <= interval.end);
+        let merged = self.intervals[first..last].iter().fold(interval, |acc, i| {
+            Interval::new(acc.start.min(i.start), acc.end.max(i.end))
+        });
+        self.intervals.splice(first..last, [merged]);
+    }
+
+    pub fn union(&self, other: &IntervalSet) -> IntervalSet {
+        let mut res = self.clone();
+        other.intervals.iter().for_each(|i| res.insert(*i));
+        res
+    }
+
+    pub fn intersection(&self, other: &IntervalSet) -> IntervalSet {
+        let mut intervals = vec![];
+        let (mut a, mut b) = (0, 0);
+        while let (Some(left), Some(right)) = (self.intervals.get(a), other.intervals.get(b)) {
+            let overlap = left.intersection(right);
+            if !overlap.is_empty() {
+                intervals.push(overlap);
+            }
+            match left.end.cmp(&right.end) {
+                Ordering::Less => a += 1,
+                Ordering::Greater => b += 1,
+                Ordering::Equal => {
+                    a += 1;
+                    b += 1;
+                }
+            }
+        }
+
+        Self { intervals }
+    }
+
+    pub fn difference(&self, other: &IntervalSet) -> IntervalSet {
+        let mut intervals = vec![];
+        let mut b = 0;
+        for interval in &self.intervals {
+            let mut start = interval.start;
+            while let Some(cut) = other.intervals.get(b).filter(|c| c.start < interval.end) {
+                if cut.end <= start {
+                    b += 1;
+                    continue;
+                }
+                if cut.start > start {
+                    intervals.push(Interval::new(start, cut.start));
+                }
+                start = start.max(cut.end);
+                if cut.end > interval.end {
+                    break;
+                }
+                b += 1;
+            }
+            if start < interval.end {
+                intervals.push(Interval::new(start, interval.end));
+            }
+        }
+
+        Self { intervals }
+    }
+
+    pub fn shift(&self, offset: i64) -> IntervalSet {
+        Self {
+            intervals: self.intervals.iter().map(|i| i.shift(offset)).collect(),
+        }
+    }
+
+    /// Moves the values covered by each `(source, offset)` piece by its
+    /// offset, leaving values outside every source where they are. When
+    /// sources overlap, the first piece covering a value wins.
+    pub fn map_offsets(&self, pieces: &[(Interval, i64)]) -> IntervalSet {
+        let mut remaining = self.clone();
+        let mut mapped = IntervalSet::new();
+        for (source, offset) in pieces {
+            let source = IntervalSet::from(*source);
+            mapped = mapped.union(&remaining.intersection(&source).shift(*offset));
+            remaining = remaining.difference(&source);
+        }
+
+        mapped.union(&remaining)
+    }
+}
+
+impl From<Interval> for IntervalSet {
+    fn from(interval: Interval) -> Self {
+        let mut set = Self::new();
+        set.insert(interval);
+        set
+    }
+}
+
+impl FromIterator<Interval> for IntervalSet {
+    fn from_iter<I: IntoIterator<Item = Interval>>(iter: I) -> Self {
+        let mut set = Self::new();
+        iter.into_iter().for_each(|i| set.insert(i));
+        set
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set(intervals: &[(i64, i64)]) -> IntervalSet {
+        intervals
+            .iter()
+            .map(|(start, end)| Interval::new(*start, *end))
+            .collect()
+    }
+
+    #[test]
+    fn test_insert() {
+        let mut s = set(&[(10, 20), (0, 5), (30, 40)]);
+        assert_eq!(s, set(&[(0, 5), (10, 20), (30, 40)]));
+        s.insert(Interval::new(5, 10));
+        assert_eq!(s, set(&[(0, 20), (30, 40)]));
+        s.insert(Interval::new(15, 35));
+        assert_eq!(s, set(&[(0, 40)]));
+        s.insert(Interval::new(50, 50));
+        assert_eq!(s.intervals().len(), 1);
+        assert_eq!(s.len(), 40);
+        assert_eq!((s.min(), s.max()), (Some(0), Some(39)));
+        assert!(s.contains(0) && s.contains(39) && !s.contains(40));
+    }
+
+    #[test]
+    fn test_set_operations() {
+        let a = set(&[(0, 10), (20, 30), (40, 50)]);
+        let b = set(&[(5, 25), (45, 60)]);
+        assert_eq!(a.union(&b), set(&[(0, 30), (40, 60)]));
+        assert_eq!(a.intersection(&b), set(&[(5, 10), (20, 25), (45, 50)]));
+        assert_eq!(a.difference(&b), set(&[(0, 5), (25, 30), (40, 45)]));
+        assert_eq!(b.difference(&a), set(&[(10, 20), (50, 60)]));
+        assert_eq!(a.difference(&set(&[(-5, 100)])), IntervalSet::new());
+        assert_eq!(a.difference(&set(&[(2, 4), (6, 8)])).len(), 26);
+        assert_eq!(a.intersection(&IntervalSet::new()), IntervalSet::new());
+    }
+
+    #[test]
+    fn test_map_offsets() {
+        let seeds = set(&[(0, 100)]);
+        let pieces = [
+            (Interval::new(10, 20), 100),
+            (Interval::new(15, 30), -15),
+            (Interval::new(90, 110), -90),
+        ];
+        assert_eq!(
+            seeds.map_offsets(&pieces),
+            set(&[(0, 15), (30, 90), (110, 120)])
+        );
+        assert_eq!(seeds.map_offsets(&[]), seeds);
+    }
+}
@@ -1,67 +1,40 @@
-pub mod grid {
-    #[derive(Debug)]
-    pub struct Grid<T> {
-        size: usize,
-        blocks: Vec<Vec<T>>,
-    }
-
-    impl<T> Grid<T> {
-        pub fn new(size: usize, blocks: Vec<Vec<T>>) -> Self {
-            Self { size, blocks }
-        }
-
-        pub fn size(&self) -> usize {
-            self.size
-        }
-
-        pub fn get(&self, point: Point) -> Option<&T> {
-            self.blocks.get(point.1).and_then(|row| row.get(point.0))
-        }
-    }
-
-    #[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, std::cmp::PartialOrd, std::cmp::Ord)]
-    pub enum Direction {
-        Up,
-        Down,
-        Left,
-        Right,
-    }
+pub mod animation;
+pub mod bitgrid;
+pub mod cycle;
+pub mod graph;
+pub mod grid;
+pub mod hyperrect;
+pub mod image;
+pub mod interner;
+pub mod interval;
+pub mod memo;
+pub mod number_theory;
+pub mod polygon;
+pub mod render;
+pub mod search;
+pub mod sparse;
 
-    #[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, std::cmp::PartialOrd, std::cmp::Ord)]
-    pub struct Point(pub usize, pub usize);
-
-    impl Point {
-        pub fn new(x: usize, y: usize) -> Self {
-            Self(x, y)
-        }
-
-        pub fn move_direction(&self, dir: Direction) -> Option<Self> {
-            let pos = match dir {
-                Direction::Up => Point(self.0, self.1.checked_sub(1)?),
-                Direction::Down => Point(self.0, self.1 + 1),
-                Direction::Left => Point(self.0.checked_sub(1)?, self.1),
-                Direction::Right => Point(self.0 + 1, self.1),
-            };
-
-            Some(pos)
-        }
-    }
-}
+pub mod config;
 
 pub mod setup {
     pub struct Args {
         pub day: usize,
         pub puzzle_input: String,
+        pub config: super::config::Config,
     }
 
     pub fn parse_args(args: &[String]) -> Result<Args, Box<dyn std::error::Error>> {
-        let day = args[1].clone().parse::<usize>()?;
+        let (positional, config) = super::config::load(args)?;
+        let day = positional.get(1).ok_or("Missing day")?.parse::<usize>()?;
 
-        let puzzle_input_path = format!("./input/day-{:0>2}.txt", day);
-        let puzzle_input_path = std::path::Path::new(&puzzle_input_path);
-        match std::fs::read_to_string(puzzle_input_path) {
-            Ok(puzzle_input) => Ok(Args { day, puzzle_input }),
-            Err(e) => Err(Box::new(e)),
+        let puzzle_input_path = config.input_path(day);
+        match std::fs::read_to_string(&puzzle_input_path) {
+            Ok(puzzle_input) => Ok(Args {
+                day,
+                puzzle_input,
+                config,
+            }),
+            Err(e) => Err(format!("{}: {}", puzzle_input_path.display(), e).into()),
         }
     }
 }
@@ -72,6 +45,7 @@ pub enum AOCResult {
     U32(u32),
     USize(usize),
     I32(i32),
+    I128(i128),
 }
 
 impl std::fmt::Display for AOCResult {
@@ -82,6 +56,7 @@ impl std::fmt::Display for AOCResult {
             AOCResult::U32(n) => write!(f, "{}", n),
             AOCResult::USize(n) => write!(f, "{}", n),
             AOCResult::I32(n) => write!(f, "{}", n),
+            AOCResult::I128(n) => write!(f, "{}", n),
         }
     }
 }
@@ -89,33 +64,177 @@ impl std::fmt::Display for AOCResult {
 pub trait Puzzle {
     fn run_part_one(&self) -> Result<AOCResult, Box<dyn std::error::Error>>;
     fn run_part_two(&self) -> Result<AOCResult, Box<dyn std::error::Error>>;
+
+    /// Draws the day's most interesting state, for days that have one.
+    fn image(&self, _scale: usize) -> Option<Result<image::Image, Box<dyn std::error::Error>>> {
+        None
+    }
+
+    /// Feeds the states of the day's simulation to `recorder`, for days
+    /// that have one.
+    fn animate(
+        &self,
+        _recorder: &mut animation::Recorder,
+    ) -> Option<Result<(), Box<dyn std::error::Error>>> {
+        None
+    }
+
+    /// The graph underlying the day's input, for days that have one.
+    fn graph(&self) -> Option<Result<graph::Graph, Box<dyn std::error::Error>>> {
+        None
+    }
+}
+
+struct PartResult {
+    result: Result<AOCResult, Box<dyn std::error::Error>>,
+    mean: std::time::Duration,
+    min: std::time::Duration,
+}
+
+impl PartResult {
+    fn measure<F>(iterations: usize, part: F) -> Self
+    where
+        F: Fn() -> Result<AOCResult, Box<dyn std::error::Error>>,
+    {
+        use std::time::{Duration, Instant};
+        let mut total = Duration::ZERO;
+        let mut min = Duration::MAX;
+        let mut result = Err("No iterations".into());
+        let mut runs = 0;
+        for _ in 0..iterations.max(1) {
+            runs += 1;
+            let t0 = Instant::now();
+            result = part();
+            let elapsed = t0.elapsed();
+            total += elapsed;
+            min = min.min(elapsed);
+            if result.is_err() {
+                break;
+            }
+        }
+
+        Self {
+            result,
+            mean: match u32::try_from(runs) {
+                Ok(runs) => total / runs,
+                Err(_) => total.div_f64(runs as f64),
+            },
+            min,
+        }
+    }
+
+    fn json(&self, timing: bool) -> String {
+        let value = match &self.result {
+            Ok(res) => format!("{{\"answer\":{}", json_string(&res.to_string())),
+            Err(e) => format!("{{\"error\":{}", json_string(&e.to_string())),
+        };
+        if timing && self.result.is_ok() {
+            format!(
+                "{},\"mean_ns\":{},\"min_ns\":{}}}",
+                value,
+                self.mean.as_nanos(),
+                self.min.as_nanos()
+            )
+        } else {
+            format!("{}}}", value)
+        }
+    }
+}
+
+fn json_string(s: &str) -> String {
+    let mut quoted = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            c if c.is_control() => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
 }
 
 impl dyn Puzzle {
-    pub fn run(&self) {
-        use std::time::Instant;
-        let t0 = Instant::now();
-        let mut total_time: std::time::Duration = std::time::Duration::ZERO;
-        match self.run_part_one() {
-            Ok(res) => {
-                let t0_elapsed = t0.elapsed();
-                total_time += t0_elapsed;
-                println!("Part 1: {}", res);
-                println!("Part 1 took: {:.2?}", t0_elapsed);
+    pub fn run(&self, config: &config::Config) {
+        let parts = [
+            PartResult::measure(config.iterations, || self.run_part_one()),
+            PartResult::measure(config.iterations, || self.run_part_two()),
+        ];
+
+        match config.format {
+            config::OutputFormat::Text => {
+                let mut total_time: std::time::Duration = std::time::Duration::ZERO;
+                for (index, part) in parts.iter().enumerate() {
+                    match &part.result {
+                        Ok(res) => {
+                            total_time += part.mean;
+                            println!("Part {}: {}", index + 1, res);
+                            if config.timing && config.iterations > 1 {
+                                println!(
+                                    "Part {} took: {:.2?} (mean of {} runs, min {:.2?})",
+                                    index + 1,
+                                    part.mean,
+                                    config.iterations,
+                                    part.min
+                                );
+                            } else if config.timing {
+                                println!("Part {} took: {:.2?}", index + 1, part.mean);
+                            }
+                        }
+                        Err(e) => println!("Part {} failed: {}", index + 1, e),
+                    }
+                }
+                if config.timing {
+                    println!("Total time: {:.2?}", total_time);
+                }
             }
-            Err(e) => println!("Part 1 failed: {}", e),
+            config::OutputFormat::Json => println!(
+                "{{\"part_one\":{},\"part_two\":{}}}",
+                parts[0].json(config.timing),
+                parts[1].json(config.timing)
+            ),
         }
-        let t1 = Instant::now();
-        match self.run_part_two() {
-            Ok(res) => {
-                let t1_elapsed = t1.elapsed();
-                total_time += t1_elapsed;
-                println!("Part 2: {}", res);
-                println!("Part 2 took: {:.2?}", t1_elapsed);
+
+        if let Some(path) = &config.image {
+            match self.image(config.image_scale) {
+                Some(Ok(image)) => {
+                    if let Err(e) = image.save(path) {
+                        eprintln!("{}: {}", path.display(), e);
+                    }
+                }
+                Some(Err(e)) => eprintln!("Image failed: {}", e),
+                None => eprintln!("No image available for this day"),
+            }
+        }
+
+        if let Some(path) = &config.animation {
+            let mut recorder =
+                animation::Recorder::new(config.image_scale, config.frame_delay, config.frame_skip);
+            match self.animate(&mut recorder) {
+                Some(Ok(())) => {
+                    if let Err(e) = recorder.save(path) {
+                        eprintln!("{}: {}", path.display(), e);
+                    }
+                }
+                Some(Err(e)) => eprintln!("Animation failed: {}", e),
+                None => eprintln!("No animation available for this day"),
+            }
+        }
+
+        if let Some(path) = &config.graph {
+            match self.graph() {
+                Some(Ok(graph)) => {
+                    let name = path.file_stem().and_then(|s| s.to_str()).unwrap_or("aoc");
+                    if let Err(e) = std::fs::write(path, graph.to_dot(name)) {
+                        eprintln!("{}: {}", path.display(), e);
+                    }
+                }
+                Some(Err(e)) => eprintln!("Graph failed: {}", e),
+                None => eprintln!("No graph available for this day"),
             }
-            Err(e) => println!("Part 2 failed: {}", e),
         }
-        println!("Total time: {:.2?}", total_time);
     }
 }
 
@@ -138,3 +257,41 @@ pub mod day_16;
 pub mod day_17;
 pub mod day_18;
 pub mod day_19;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_measure_stops_at_failure() {
+        let calls = std::cell::Cell::new(0);
+        let part = PartResult::measure(5, || {
+            calls.set(calls.get() + 1);
+            std::thread::sleep(std::time::Duration::from_millis(2));
+            Err("bad input".into())
+        });
+        assert_eq!(calls.get(), 1);
+        assert!(part.result.is_err());
+        assert_eq!(part.mean, part.min);
+    }
+
+    #[test]
+    fn test_json_escaping() {
+        let part = PartResult {
+            result: Err("bad \"node\" at C:\\in\n\u{1b}".into()),
+            mean: std::time::Duration::ZERO,
+            min: std::time::Duration::ZERO,
+        };
+        assert_eq!(
+            part.json(false),
+            r#"{"error":"bad \"node\" at C:\\in\n\u001b"}"#
+        );
+
+        let part = PartResult {
+            result: Ok(AOCResult::I128(-2)),
+            mean: std::time::Duration::from_nanos(5),
+            min: std::time::Duration::from_nanos(3),
+        };
+        assert_eq!(part.json(true), r#"{"answer":"-2","mean_ns":5,"min_ns":3}"#);
+    }
+}
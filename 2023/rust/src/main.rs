@@ -3,6 +3,9 @@ use advent_of_code_2023::*;
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     let args: Vec<String> = std::env::args().collect();
     let args = setup::parse_args(&args)?;
+    if args.config.year != 2023 {
+        return Err(format!("Year not implemented: {}", args.config.year).into());
+    }
     if let Some(puzzle) = match args.day {
         1 => Some(day_01::Puzzle::create(args.puzzle_input)),
         2 => Some(day_02::Puzzle::create(args.puzzle_input)),
@@ -28,7 +31,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             None
         }
     } {
-        puzzle.run();
+        puzzle.run(&args.config);
     }
     Ok(())
 }
@@ -0,0 +1,156 @@
+use std::collections::{HashMap, VecDeque};
+use std::hash::Hash;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Stats {
+    pub hits: usize,
+    pub misses: usize,
+    pub evictions: usize,
+}
+
+/// Cache for recursive functions. The closure computing a missing value
+/// gets the memo back, so it can recurse through it:
+///
+/// ```
+/// use advent_of_code_2023::memo::Memo;
+///
+/// fn fib(n: u64, memo: &mut Memo<u64, u64>) -> u64 {
+///     match n {
+///         0 | 1 => n,
+///         _ => memo.get_or_insert_with(n, |memo| fib(n - 1, memo) + fib(n - 2, memo)),
+///     }
+/// }
+///
+/// assert_eq!(fib(90, &mut Memo::new()), 2880067194370816120);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Memo<K, V> {
+    cache: HashMap<K, V>,
+    capacity: Option<usize>,
+    insertion_order: VecDeque<K>,
+    stats: Stats,
+}
+
+impl<K, V> Default for Memo<K, V> {
+    fn default() -> Self {
+        Self {
+            cache: HashMap::new(),
+            capacity: None,
+            insertion_order: VecDeque::new(),
+            stats: Stats::default(),
+        }
+    }
+}
+
+impl<K, V> Memo<K, V>
+where
+    K: Hash + Eq + Clone,
+    V: Clone,
+{
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A memo holding at most `capacity` values, forgetting the oldest one
+    /// when full.
+    pub fn bounded(capacity: usize) -> Self {
+        Self {
+            capacity: Some(capacity),
+            ..Self::default()
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.cache.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cache.is_empty()
+    }
+
+    pub fn stats(&self) -> Stats {
+        self.stats
+    }
+
+    pub fn clear(&mut self) {
+        self.cache.clear();
+        self.insertion_order.clear();
+    }
+
+    pub fn get_or_insert_with<F>(&mut self, key: K, compute: F) -> V
+    where
+        F: FnOnce(&mut Self) -> V,
+    {
+        if let Some(value) = self.cache.get(&key) {
+            self.stats.hits += 1;
+            return value.clone();
+        }
+
+        self.stats.misses += 1;
+        let value = compute(self);
+        self.insert(key, value.clone());
+        value
+    }
+
+    fn insert(&mut self, key: K, value: V) {
+        if self.capacity == Some(0) {
+            return;
+        }
+        // A recursive call may already have stored this key.
+        if self.cache.insert(key.clone(), value).is_some() {
+            return;
+        }
+
+        self.insertion_order.push_back(key);
+        if self.capacity.is_some_and(|c| self.cache.len() > c) {
+            if let Some(oldest) = self.insertion_order.pop_front() {
+                self.cache.remove(&oldest);
+                self.stats.evictions += 1;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fib(n: u64, memo: &mut Memo<u64, u64>) -> u64 {
+        match n {
+            0 | 1 => n,
+            _ => memo.get_or_insert_with(n, |memo| fib(n - 1, memo) + fib(n - 2, memo)),
+        }
+    }
+
+    #[test]
+    fn test_stats() {
+        let mut memo = Memo::new();
+        assert_eq!(fib(50, &mut memo), 12586269025);
+        assert_eq!(memo.len(), 49);
+        assert_eq!(
+            memo.stats(),
+            Stats {
+                hits: 47,
+                misses: 49,
+                evictions: 0
+            }
+        );
+
+        assert_eq!(fib(50, &mut memo), 12586269025);
+        assert_eq!(memo.stats().hits, 48);
+        memo.clear();
+        assert!(memo.is_empty());
+    }
+
+    #[test]
+    fn test_bounded() {
+        let mut memo = Memo::bounded(3);
+        assert_eq!(fib(50, &mut memo), 12586269025);
+        assert_eq!(memo.len(), 3);
+        assert_eq!(memo.stats().evictions, 46);
+
+        let mut memo = Memo::bounded(0);
+        assert_eq!(fib(20, &mut memo), 6765);
+        assert!(memo.is_empty());
+    }
+}
@@ -0,0 +1,256 @@
+use std::fmt;
+use std::ops::{Add, Mul};
+
+pub fn gcd(a: u128, b: u128) -> u128 {
+    let (mut a, mut b) = (a, b);
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+/// Panics if the result doesn't fit in `u128`.
+pub fn lcm(a: u128, b: u128) -> u128 {
+    match (a, b) {
+        (0, _) | (_, 0) => 0,
+        _ => a / gcd(a, b) * b,
+    }
+}
+
+/// Zero for an empty slice.
+pub fn gcd_all(nums: &[u128]) -> u128 {
+    nums.iter().fold(0, |acc, n| gcd(acc, *n))
+}
+
+/// One for an empty slice.
+pub fn lcm_all(nums: &[u128]) -> u128 {
+    nums.iter().fold(1, |acc, n| lcm(acc, *n))
+}
+
+/// Returns `(g, x, y)` with `a * x + b * y == g`, where `g` is the
+/// non-negative gcd of `a` and `b`.
+pub fn extended_gcd(a: i128, b: i128) -> (i128, i128, i128) {
+    let (mut old_r, mut r) = (a, b);
+    let (mut old_x, mut x) = (1, 0);
+    let (mut old_y, mut y) = (0, 1);
+    while r != 0 {
+        let q = old_r / r;
+        (old_r, r) = (r, old_r - q * r);
+        (old_x, x) = (x, old_x - q * x);
+        (old_y, y) = (y, old_y - q * y);
+    }
+
+    match old_r < 0 {
+        true => (-old_r, -old_x, -old_y),
+        false => (old_r, old_x, old_y),
+    }
+}
+
+/// The `x` in `0..m` with `a * x ≡ 1 (mod m)`, if `a` and `m` are coprime.
+pub fn mod_inverse(a: i128, m: i128) -> Option<i128> {
+    let (g, x, _) = extended_gcd(a.rem_euclid(m), m);
+    (g == 1).then(|| x.rem_euclid(m))
+}
+
+/// Solves the system `x ≡ residue (mod modulus)` for every pair, returning
+/// `(x, lcm of the moduli)` with `x` in `0..lcm`. The moduli need not be
+/// coprime; `None` means the congruences contradict each other.
+pub fn crt(congruences: &[(i128, i128)]) -> Option<(i128, i128)> {
+    congruences.iter().try_fold((0, 1), |(r1, m1), &(r2, m2)| {
+        let (g, p, _) = extended_gcd(m1, m2);
+        let diff = r2 - r1;
+        if diff % g != 0 {
+            return None;
+        }
+
+        // m1 * p ≡ g (mod m2), so stepping r1 by m1 * p * diff / g lands
+        // on r2 modulo m2 while staying on r1 modulo m1.
+        let step = m2 / g;
+        let k = (diff / g % step * (p % step)).rem_euclid(step);
+        let modulus = m1 * step;
+        Some(((r1 + m1 * k).rem_euclid(modulus), modulus))
+    })
+}
+
+/// An exact fraction kept in lowest terms with a positive denominator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rational {
+    numerator: i128,
+    denominator: i128,
+}
+
+impl Rational {
+    pub const ZERO: Rational = Rational {
+        numerator: 0,
+        denominator: 1,
+    };
+
+    /// Panics if `denominator` is zero or the reduced fraction doesn't fit.
+    pub fn new(numerator: i128, denominator: i128) -> Self {
+        assert!(denominator != 0, "Zero denominator");
+        Self::reduced(numerator, denominator).expect("Rational overflow")
+    }
+
+    fn reduced(numerator: i128, denominator: i128) -> Option<Self> {
+        let (n, d) = (numerator.unsigned_abs(), denominator.unsigned_abs());
+        let g = gcd(n, d);
+        let numerator = match (numerator < 0) != (denominator < 0) {
+            true => 0i128.checked_sub_unsigned(n / g)?,
+            false => i128::try_from(n / g).ok()?,
+        };
+        Some(Self {
+            numerator,
+            denominator: i128::try_from(d / g).ok()?,
+        })
+    }
+
+    /// `None` if the result or an intermediate product overflows.
+    pub fn checked_add(self, other: Rational) -> Option<Rational> {
+        let g = gcd(
+            self.denominator.unsigned_abs(),
+            other.denominator.unsigned_abs(),
+        ) as i128;
+        let (a, b) = (self.denominator / g, other.denominator / g);
+        Self::reduced(
+            self.numerator
+                .checked_mul(b)?
+                .checked_add(other.numerator.checked_mul(a)?)?,
+            a.checked_mul(other.denominator)?,
+        )
+    }
+
+    /// `None` if the result overflows.
+    pub fn checked_mul(self, other: Rational) -> Option<Rational> {
+        // Cancel across the diagonal first to keep the products small.
+        let a = Self::reduced(self.numerator, other.denominator)?;
+        let b = Self::reduced(other.numerator, self.denominator)?;
+        Self::reduced(
+            a.numerator.checked_mul(b.numerator)?,
+            a.denominator.checked_mul(b.denominator)?,
+        )
+    }
+
+    pub fn numerator(&self) -> i128 {
+        self.numerator
+    }
+
+    pub fn denominator(&self) -> i128 {
+        self.denominator
+    }
+
+    pub fn is_integer(&self) -> bool {
+        self.denominator == 1
+    }
+}
+
+impl From<i128> for Rational {
+    fn from(n: i128) -> Self {
+        Self::new(n, 1)
+    }
+}
+
+impl Add for Rational {
+    type Output = Rational;
+
+    fn add(self, other: Rational) -> Rational {
+        self.checked_add(other).expect("Rational overflow")
+    }
+}
+
+impl Mul for Rational {
+    type Output = Rational;
+
+    fn mul(self, other: Rational) -> Rational {
+        self.checked_mul(other).expect("Rational overflow")
+    }
+}
+
+impl fmt::Display for Rational {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.is_integer() {
+            true => write!(f, "{}", self.numerator),
+            false => write!(f, "{}/{}", self.numerator, self.denominator),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lcm() {
+        assert_eq!(lcm_all(&[2, 3]), 6);
+        assert_eq!(lcm_all(&[21, 6]), 42);
+        assert_eq!(lcm_all(&[1, 3, 8, 10]), 120);
+        assert_eq!(lcm_all(&[]), 1);
+        assert_eq!(gcd_all(&[12, 18, 27]), 3);
+        assert_eq!(gcd_all(&[]), 0);
+        assert_eq!(gcd(0, 5), 5);
+    }
+
+    #[test]
+    fn test_extended_gcd() {
+        for (a, b) in [(240, 46), (-240, 46), (7, 0), (0, -7), (17, 5)] {
+            let (g, x, y) = extended_gcd(a, b);
+            assert_eq!(g, gcd(a.unsigned_abs(), b.unsigned_abs()) as i128);
+            assert_eq!(a * x + b * y, g);
+        }
+    }
+
+    #[test]
+    fn test_mod_inverse() {
+        assert_eq!(mod_inverse(3, 11), Some(4));
+        assert_eq!(mod_inverse(-3, 11), Some(7));
+        assert_eq!(mod_inverse(6, 9), None);
+        assert_eq!(mod_inverse(1, 1), Some(0));
+    }
+
+    #[test]
+    fn test_crt() {
+        assert_eq!(crt(&[(2, 3), (3, 5), (2, 7)]), Some((23, 105)));
+        assert_eq!(crt(&[(2, 4), (4, 6)]), Some((10, 12)));
+        assert_eq!(crt(&[(1, 4), (2, 6)]), None);
+        assert_eq!(crt(&[]), Some((0, 1)));
+        assert_eq!(crt(&[(-1, 5)]), Some((4, 5)));
+
+        let big = 1_000_000_007;
+        assert_eq!(crt(&[(5, big), (7, big * 2)]), None);
+        let (x, m) = crt(&[(5, big), (big + 5, big * 2), (3, 998_244_353)]).unwrap();
+        assert_eq!(m, big * 2 * 998_244_353);
+        assert_eq!((x % big, x % (big * 2), x % 998_244_353), (5, big + 5, 3));
+    }
+
+    #[test]
+    fn test_rational() {
+        let half = Rational::new(2, 4);
+        assert_eq!((half.numerator(), half.denominator()), (1, 2));
+        assert_eq!(Rational::new(3, -6), Rational::new(-1, 2));
+        assert_eq!(half + Rational::new(1, 3), Rational::new(5, 6));
+        assert_eq!(half + Rational::new(-1, 2), Rational::ZERO);
+        assert_eq!(
+            Rational::new(-2, 3) * Rational::new(9, 4),
+            Rational::new(-3, 2)
+        );
+        assert_eq!(Rational::new(0, -5), Rational::ZERO);
+        assert!(Rational::new(6, 3).is_integer());
+        assert_eq!(Rational::new(-7, 21).to_string(), "-1/3");
+        assert_eq!(Rational::from(4).to_string(), "4");
+    }
+
+    #[test]
+    fn test_rational_overflow() {
+        let big = Rational::new(i128::MAX, 2);
+        assert_eq!(big.checked_add(big), None);
+        assert_eq!(big.checked_mul(Rational::from(3)), None);
+        assert_eq!(
+            big.checked_mul(Rational::new(2, 3)),
+            Some(Rational::new(i128::MAX, 3))
+        );
+        assert_eq!(
+            Rational::new(1, i128::MAX).checked_add(Rational::new(1, i128::MAX - 1)),
+            None
+        );
+        assert_eq!(Rational::new(i128::MIN, 2), Rational::new(i128::MIN / 2, 1));
+    }
+}
@@ -0,0 +1,310 @@
+use crate::grid::{Direction, SignedPoint};
+use crate::number_theory::gcd;
+use std::cmp::Ordering;
+
+/// A simple polygon on the integer lattice. The last vertex connects back to
+/// the first one. The shoelace sum is taken in `i128` with coordinates
+/// measured from the first vertex. Each of its terms is at most
+/// `2 * width * height`, the spans of the vertices, so it can't overflow
+/// while `2 * vertices * width * height` stays below 2^127; past that the
+/// area methods may return `None`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Polygon {
+    vertices: Vec<SignedPoint>,
+}
+
+impl Polygon {
+    /// A closing vertex equal to the first one is dropped.
+    pub fn new(mut vertices: Vec<SignedPoint>) -> Self {
+        if vertices.len() > 1 && vertices.first() == vertices.last() {
+            vertices.pop();
+        }
+        Self { vertices }
+    }
+
+    /// Walks from `start` taking `length` steps in each direction, returning
+    /// `None` if a coordinate overflows.
+    pub fn from_steps<I>(start: SignedPoint, steps: I) -> Option<Self>
+    where
+        I: IntoIterator<Item = (Direction, i64)>,
+    {
+        let mut position = start;
+        let mut vertices = vec![start];
+        for (direction, length) in steps {
+            let delta = direction.delta();
+            position = SignedPoint(
+                position.0.checked_add(delta.0.checked_mul(length)?)?,
+                position.1.checked_add(delta.1.checked_mul(length)?)?,
+            );
+            vertices.push(position);
+        }
+        Some(Self::new(vertices))
+    }
+
+    pub fn vertices(&self) -> &[SignedPoint] {
+        &self.vertices
+    }
+
+    fn edges(&self) -> impl Iterator<Item = (SignedPoint, SignedPoint)> + '_ {
+        self.vertices
+            .iter()
+            .zip(self.vertices.iter().cycle().skip(1))
+            .map(|(a, b)| (*a, *b))
+    }
+
+    /// Shoelace sum, positive for counter-clockwise vertices when the y axis
+    /// points up, or `None` if it overflows.
+    pub fn signed_double_area(&self) -> Option<i128> {
+        let origin = *self.vertices.first()?;
+        let relative = |p: SignedPoint| {
+            (
+                p.0 as i128 - origin.0 as i128,
+                p.1 as i128 - origin.1 as i128,
+            )
+        };
+        self.edges().try_fold(0i128, |sum, (a, b)| {
+            let (a, b) = (relative(a), relative(b));
+            let cross = a.0.checked_mul(b.1)?.checked_sub(b.0.checked_mul(a.1)?)?;
+            sum.checked_add(cross)
+        })
+    }
+
+    /// Twice the enclosed area, which is always an integer for lattice
+    /// polygons.
+    pub fn double_area(&self) -> Option<u128> {
+        self.signed_double_area().map(i128::unsigned_abs)
+    }
+
+    /// Enclosed area, rounded down for polygons with a half-integer area.
+    pub fn area(&self) -> Option<u128> {
+        self.double_area().map(|a| a / 2)
+    }
+
+    /// Sum of the edge lengths, measured in taxicab distance so it matches
+    /// the Euclidean perimeter of rectilinear polygons.
+    pub fn perimeter(&self) -> u128 {
+        self.edges().map(|(a, b)| a.manhattan_distance(b)).sum()
+    }
+
+    /// Lattice points lying on the edges.
+    pub fn boundary_points(&self) -> u128 {
+        self.edges()
+            .map(|(a, b)| gcd(a.0.abs_diff(b.0) as u128, a.1.abs_diff(b.1) as u128))
+            .sum()
+    }
+
+    /// Lattice points strictly inside, by Pick's theorem. Zero for
+    /// degenerate polygons, such as a segment walked out and back, where the
+    /// theorem doesn't apply.
+    pub fn interior_points(&self) -> Option<u128> {
+        let double_area = self.double_area()?;
+        let interior = (double_area + 2).checked_sub(self.boundary_points());
+        Some(match double_area {
+            0 => 0,
+            _ => interior.map_or(0, |n| n / 2),
+        })
+    }
+
+    /// Lattice points inside or on the boundary. Unlike adding up
+    /// `interior_points` and `boundary_points`, this stays right for paths
+    /// that retrace an edge, counting each point once.
+    pub fn enclosed_points(&self) -> Option<u128> {
+        Some(self.double_area()?.checked_add(self.boundary_points())? / 2 + 1)
+    }
+
+    pub fn on_boundary(&self, point: SignedPoint) -> bool {
+        self.edges().any(|(a, b)| {
+            let (dx, dy) = (b.0 as i128 - a.0 as i128, b.1 as i128 - a.1 as i128);
+            let (px, py) = (point.0 as i128 - a.0 as i128, point.1 as i128 - a.1 as i128);
+            compare_products(dx, py, dy, px) == Ordering::Equal
+                && (a.0.min(b.0)..=a.0.max(b.0)).contains(&point.0)
+                && (a.1.min(b.1)..=a.1.max(b.1)).contains(&point.1)
+        })
+    }
+
+    /// Whether `point` lies inside the polygon or on its boundary.
+    pub fn contains(&self, point: SignedPoint) -> bool {
+        if self.on_boundary(point) {
+            return true;
+        }
+
+        // Cast a ray towards +x and count the edges crossing it, treating
+        // each edge as half-open in y so shared vertices count once.
+        self.edges()
+            .filter(|(a, b)| (a.1 > point.1) != (b.1 > point.1))
+            .filter(|(a, b)| {
+                let (dx, dy) = (b.0 as i128 - a.0 as i128, b.1 as i128 - a.1 as i128);
+                let (px, py) = (point.0 as i128 - a.0 as i128, point.1 as i128 - a.1 as i128);
+                let side = compare_products(px, dy, py, dx);
+                match dy > 0 {
+                    true => side == Ordering::Less,
+                    false => side == Ordering::Greater,
+                }
+            })
+            .count()
+            % 2
+            == 1
+    }
+}
+
+/// Compares `a * b` with `c * d` for factors that are differences of two
+/// `i64`s, whose products can exceed `i128` but not `u128` in magnitude.
+fn compare_products(a: i128, b: i128, c: i128, d: i128) -> Ordering {
+    let sign = |x: i128, y: i128| x.signum() * y.signum();
+    let (left, right) = (sign(a, b), sign(c, d));
+    if left != right {
+        return left.cmp(&right);
+    }
+
+    let magnitude = |x: i128, y: i128| x.unsigned_abs() * y.unsigned_abs();
+    match left {
+        1 => magnitude(a, b).cmp(&magnitude(c, d)),
+        -1 => magnitude(c, d).cmp(&magnitude(a, b)),
+        _ => Ordering::Equal,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn square(size: i64) -> Polygon {
+        Polygon::from_steps(
+            SignedPoint(0, 0),
+            [
+                (Direction::Right, size),
+                (Direction::Down, size),
+                (Direction::Left, size),
+                (Direction::Up, size),
+            ],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_measurements() {
+        let polygon = square(4);
+        assert_eq!(polygon.vertices().len(), 4);
+        assert_eq!(polygon.area(), Some(16));
+        assert_eq!(polygon.perimeter(), 16);
+        assert_eq!(polygon.boundary_points(), 16);
+        assert_eq!(polygon.interior_points(), Some(9));
+
+        let triangle = Polygon::new(vec![
+            SignedPoint(0, 0),
+            SignedPoint(4, 0),
+            SignedPoint(0, 3),
+        ]);
+        assert_eq!(triangle.signed_double_area(), Some(12));
+        assert_eq!(triangle.boundary_points(), 4 + 3 + 1);
+        assert_eq!(triangle.interior_points(), Some(3));
+        assert_eq!(Polygon::new(vec![]).area(), None);
+        assert_eq!(triangle.enclosed_points(), Some(3 + 8));
+    }
+
+    #[test]
+    fn test_degenerate() {
+        let segment = Polygon::new(vec![SignedPoint(0, 0), SignedPoint(4, 0)]);
+        assert_eq!(segment.area(), Some(0));
+        assert_eq!(segment.perimeter(), 8);
+        assert_eq!(segment.interior_points(), Some(0));
+        assert_eq!(segment.enclosed_points(), Some(5));
+
+        let collinear = Polygon::new(vec![
+            SignedPoint(0, 0),
+            SignedPoint(2, 2),
+            SignedPoint(5, 5),
+        ]);
+        assert_eq!(collinear.interior_points(), Some(0));
+        assert_eq!(collinear.enclosed_points(), Some(6));
+
+        // A unit square with a long spur retraced off one corner.
+        let spur = Polygon::new(vec![
+            SignedPoint(0, 0),
+            SignedPoint(1, 0),
+            SignedPoint(1, 1),
+            SignedPoint(0, 1),
+            SignedPoint(0, 10),
+            SignedPoint(0, 1),
+        ]);
+        assert_eq!(spur.interior_points(), Some(0));
+        assert_eq!(spur.enclosed_points(), Some(4 + 9));
+
+        let point = Polygon::new(vec![SignedPoint(3, -3)]);
+        assert_eq!(point.perimeter(), 0);
+        assert_eq!(point.interior_points(), Some(0));
+        assert_eq!(point.enclosed_points(), Some(1));
+    }
+
+    #[test]
+    fn test_overflow() {
+        let huge = square(i64::MAX);
+        assert_eq!(huge.area(), Some(i64::MAX as u128 * i64::MAX as u128));
+        assert_eq!(huge.perimeter(), 4 * i64::MAX as u128);
+        assert_eq!(
+            huge.interior_points(),
+            Some((i64::MAX as u128 - 1) * (i64::MAX as u128 - 1))
+        );
+
+        // Far from the origin, but small relative to the first vertex.
+        let offset = Polygon::new(vec![
+            SignedPoint(i64::MIN, i64::MAX),
+            SignedPoint(i64::MIN + 4, i64::MAX),
+            SignedPoint(i64::MIN + 4, i64::MAX - 3),
+        ]);
+        assert_eq!(offset.area(), Some(6));
+
+        // Doubled, this area needs about 2^129.
+        let widest = Polygon::new(vec![
+            SignedPoint(i64::MIN, i64::MIN),
+            SignedPoint(i64::MAX, i64::MIN),
+            SignedPoint(i64::MAX, i64::MAX),
+            SignedPoint(i64::MIN, i64::MAX),
+        ]);
+        assert_eq!(widest.signed_double_area(), None);
+        assert_eq!(widest.perimeter(), 4 * u64::MAX as u128);
+        assert_eq!(widest.interior_points(), None);
+        assert_eq!(
+            Polygon::from_steps(SignedPoint(1, 0), [(Direction::Right, i64::MAX)]),
+            None
+        );
+
+        let triangle = Polygon::new(vec![
+            SignedPoint(i64::MIN, i64::MIN),
+            SignedPoint(i64::MAX, i64::MAX),
+            SignedPoint(i64::MIN, i64::MAX),
+        ]);
+        assert!(triangle.on_boundary(SignedPoint(0, 0)));
+        assert!(triangle.on_boundary(SignedPoint(i64::MAX - 1, i64::MAX - 1)));
+        assert!(!triangle.on_boundary(SignedPoint(i64::MAX - 2, i64::MAX - 1)));
+        assert!(triangle.contains(SignedPoint(i64::MAX - 2, i64::MAX - 1)));
+        assert!(!triangle.contains(SignedPoint(i64::MAX - 1, i64::MAX - 2)));
+    }
+
+    #[test]
+    fn test_contains() {
+        // An L shape:
+        // ###.
+        // #.#.
+        // #.##
+        // ####
+        let polygon = Polygon::new(vec![
+            SignedPoint(0, 0),
+            SignedPoint(2, 0),
+            SignedPoint(2, 2),
+            SignedPoint(3, 2),
+            SignedPoint(3, 3),
+            SignedPoint(0, 3),
+        ]);
+        assert!(polygon.contains(SignedPoint(1, 1)));
+        assert!(polygon.contains(SignedPoint(1, 2)));
+        assert!(polygon.on_boundary(SignedPoint(3, 3)));
+        assert!(polygon.contains(SignedPoint(2, 1)));
+        assert!(!polygon.on_boundary(SignedPoint(1, 1)));
+        assert!(!polygon.contains(SignedPoint(3, 1)));
+        assert!(!polygon.contains(SignedPoint(3, 0)));
+        assert!(!polygon.contains(SignedPoint(-1, 2)));
+        assert!(!polygon.contains(SignedPoint(4, 2)));
+        assert_eq!(polygon.interior_points(), Some(2));
+    }
+}
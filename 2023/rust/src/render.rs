@@ -0,0 +1,177 @@
+use crate::grid::{Grid, Point};
+use std::collections::HashMap;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Color {
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+    White,
+    Rgb(u8, u8, u8),
+}
+
+impl Color {
+    fn ansi(&self) -> String {
+        match self {
+            Color::Red => "31".to_string(),
+            Color::Green => "32".to_string(),
+            Color::Yellow => "33".to_string(),
+            Color::Blue => "34".to_string(),
+            Color::Magenta => "35".to_string(),
+            Color::Cyan => "36".to_string(),
+            Color::White => "37".to_string(),
+            Color::Rgb(r, g, b) => format!("38;2;{};{};{}", r, g, b),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct Style {
+    glyph: Option<char>,
+    color: Option<Color>,
+}
+
+type CellFormat<'a, T> = Box<dyn Fn(Point, &T) -> char + 'a>;
+
+/// Draws a `Grid` as text, one character per cell, with optional overlays
+/// painted on top. Later overlays win where they overlap.
+pub struct Renderer<'a, T> {
+    grid: &'a Grid<T>,
+    format: CellFormat<'a, T>,
+    overlay: HashMap<Point, Style>,
+    colors: bool,
+}
+
+impl<'a, T> Renderer<'a, T> {
+    /// Colours are enabled only when stdout is a terminal.
+    pub fn new<F>(grid: &'a Grid<T>, format: F) -> Self
+    where
+        F: Fn(Point, &T) -> char + 'a,
+    {
+        use std::io::IsTerminal;
+        Self {
+            grid,
+            format: Box::new(format),
+            overlay: HashMap::new(),
+            colors: std::io::stdout().is_terminal(),
+        }
+    }
+
+    pub fn colors(mut self, enabled: bool) -> Self {
+        self.colors = enabled;
+        self
+    }
+
+    /// Marks every point with `glyph` and/or `color`, leaving whichever is
+    /// `None` as drawn by earlier layers.
+    pub fn highlight<I>(mut self, points: I, glyph: Option<char>, color: Option<Color>) -> Self
+    where
+        I: IntoIterator<Item = Point>,
+    {
+        points.into_iter().for_each(|point| {
+            let style = self.overlay.entry(point).or_default();
+            style.glyph = glyph.or(style.glyph);
+            style.color = color.or(style.color);
+        });
+        self
+    }
+
+    /// Draws a walk through the grid as arrows pointing to the next step.
+    pub fn path<I>(mut self, points: I, color: Option<Color>) -> Self
+    where
+        I: IntoIterator<Item = Point>,
+    {
+        let points = points.into_iter().collect::<Vec<_>>();
+        let steps = points.windows(2).map(|w| {
+            let glyph = match (w[1].0 as i64 - w[0].0 as i64, w[1].1 as i64 - w[0].1 as i64) {
+                (0, dy) if dy < 0 => '^',
+                (0, dy) if dy > 0 => 'v',
+                (dx, 0) if dx < 0 => '<',
+                (dx, 0) if dx > 0 => '>',
+                _ => '*',
+            };
+            (w[0], glyph)
+        });
+        let end = points.last().map(|point| (*point, '*'));
+        for (point, glyph) in steps.chain(end).collect::<Vec<_>>() {
+            self = self.highlight([point], Some(glyph), color);
+        }
+        self
+    }
+}
+
+impl<T> fmt::Display for Renderer<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.grid.iter_cells().try_for_each(|(point, cell)| {
+            if point.0 == 0 && point.1 > 0 {
+                writeln!(f)?;
+            }
+
+            let style = self.overlay.get(&point).copied().unwrap_or_default();
+            let glyph = style.glyph.unwrap_or_else(|| (self.format)(point, cell));
+            match style.color {
+                Some(color) if self.colors => write!(f, "\x1b[{}m{}\x1b[0m", color.ansi(), glyph),
+                _ => write!(f, "{}", glyph),
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAP: &str = "....
+.##.
+....";
+
+    #[test]
+    fn test_render_plain() {
+        let grid = MAP.parse::<Grid<char>>().unwrap();
+        let renderer = Renderer::new(&grid, |_, c| *c).colors(false);
+        assert_eq!(renderer.to_string(), MAP);
+
+        let renderer = Renderer::new(&grid, |_, c| if *c == '#' { '█' } else { ' ' })
+            .colors(false)
+            .highlight([Point(1, 1)], Some('O'), Some(Color::Red))
+            .highlight([Point(0, 0), Point(3, 2)], None, Some(Color::Green));
+        assert_eq!(renderer.to_string(), "    \n O█ \n    ");
+    }
+
+    #[test]
+    fn test_render_path() {
+        let grid = MAP.parse::<Grid<char>>().unwrap();
+        let path = [
+            Point(0, 2),
+            Point(0, 1),
+            Point(0, 0),
+            Point(1, 0),
+            Point(2, 0),
+        ];
+        let renderer = Renderer::new(&grid, |_, c| *c)
+            .colors(false)
+            .path(path, None);
+        assert_eq!(renderer.to_string(), ">>*.\n^##.\n^...");
+    }
+
+    #[test]
+    fn test_render_colors() {
+        let grid = "ab".parse::<Grid<char>>().unwrap();
+        let renderer = Renderer::new(&grid, |_, c| *c).colors(true).highlight(
+            [Point(1, 0)],
+            None,
+            Some(Color::Rgb(255, 128, 0)),
+        );
+        assert_eq!(renderer.to_string(), "a\x1b[38;2;255;128;0mb\x1b[0m");
+
+        let renderer = renderer.highlight([Point(0, 0)], None, Some(Color::Yellow));
+        assert_eq!(
+            renderer.to_string(),
+            "\x1b[33ma\x1b[0m\x1b[38;2;255;128;0mb\x1b[0m"
+        );
+    }
+}
@@ -0,0 +1,522 @@
+use crate::grid::{Connectivity, Grid, Point};
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, VecDeque};
+use std::hash::Hash;
+use std::ops::Add;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Path<S, C> {
+    pub cost: C,
+    /// Every state visited, from the start up to and including the goal.
+    pub states: Vec<S>,
+}
+
+trait Frontier<C> {
+    fn push(&mut self, priority: C, id: usize);
+    fn pop(&mut self) -> Option<usize>;
+}
+
+struct Heap<C>(BinaryHeap<Reverse<(C, usize)>>);
+
+impl<C: Ord> Frontier<C> for Heap<C> {
+    fn push(&mut self, priority: C, id: usize) {
+        self.0.push(Reverse((priority, id)));
+    }
+
+    fn pop(&mut self) -> Option<usize> {
+        self.0.pop().map(|Reverse((_, id))| id)
+    }
+}
+
+/// A monotone priority queue with one bucket per integer priority, which
+/// avoids the heap's log factor when edge costs are small.
+struct Buckets {
+    buckets: Vec<Vec<usize>>,
+    current: usize,
+}
+
+impl Frontier<usize> for Buckets {
+    fn push(&mut self, priority: usize, id: usize) {
+        let index = priority.max(self.current);
+        if index >= self.buckets.len() {
+            self.buckets.resize_with(index + 1, Vec::new);
+        }
+        self.buckets[index].push(id);
+    }
+
+    fn pop(&mut self) -> Option<usize> {
+        while self.current < self.buckets.len() {
+            if let Some(id) = self.buckets[self.current].pop() {
+                return Some(id);
+            }
+            self.current += 1;
+        }
+
+        None
+    }
+}
+
+fn search<S, C, Q, I, N, FN, FH, FG>(
+    mut frontier: Q,
+    starts: I,
+    mut successors: FN,
+    mut heuristic: FH,
+    mut is_goal: FG,
+) -> Option<Path<S, C>>
+where
+    S: Clone + Eq + Hash,
+    C: Copy + Ord + Add<Output = C> + Default,
+    Q: Frontier<C>,
+    I: IntoIterator<Item = S>,
+    FN: FnMut(&S) -> N,
+    N: IntoIterator<Item = (S, C)>,
+    FH: FnMut(&S) -> C,
+    FG: FnMut(&S) -> bool,
+{
+    let mut ids: HashMap<S, usize> = HashMap::new();
+    let mut states: Vec<S> = vec![];
+    let mut costs: Vec<C> = vec![];
+    let mut parents: Vec<Option<usize>> = vec![];
+    let mut closed: Vec<bool> = vec![];
+
+    for start in starts {
+        if ids.contains_key(&start) {
+            continue;
+        }
+        let id = states.len();
+        frontier.push(heuristic(&start), id);
+        ids.insert(start.clone(), id);
+        states.push(start);
+        costs.push(C::default());
+        parents.push(None);
+        closed.push(false);
+    }
+
+    while let Some(id) = frontier.pop() {
+        if closed[id] {
+            continue;
+        }
+        closed[id] = true;
+
+        if is_goal(&states[id]) {
+            let mut path = vec![];
+            let mut current = Some(id);
+            while let Some(id) = current {
+                path.push(states[id].clone());
+                current = parents[id];
+            }
+            path.reverse();
+
+            return Some(Path {
+                cost: costs[id],
+                states: path,
+            });
+        }
+
+        for (next, step_cost) in successors(&states[id]) {
+            let cost = costs[id] + step_cost;
+            let next_id = match ids.get(&next) {
+                Some(&next_id) if closed[next_id] || costs[next_id] <= cost => continue,
+                Some(&next_id) => next_id,
+                None => {
+                    let next_id = states.len();
+                    ids.insert(next.clone(), next_id);
+                    states.push(next);
+                    costs.push(cost);
+                    parents.push(None);
+                    closed.push(false);
+                    next_id
+                }
+            };
+
+            costs[next_id] = cost;
+            parents[next_id] = Some(id);
+            frontier.push(cost + heuristic(&states[next_id]), next_id);
+        }
+    }
+
+    None
+}
+
+/// Finds the cheapest path from any of `starts` to a state satisfying
+/// `is_goal`, where `successors` yields each neighbouring state and the cost
+/// of stepping to it.
+pub fn dijkstra<S, C, I, N, FN, FG>(starts: I, successors: FN, is_goal: FG) -> Option<Path<S, C>>
+where
+    S: Clone + Eq + Hash,
+    C: Copy + Ord + Add<Output = C> + Default,
+    I: IntoIterator<Item = S>,
+    FN: FnMut(&S) -> N,
+    N: IntoIterator<Item = (S, C)>,
+    FG: FnMut(&S) -> bool,
+{
+    search(
+        Heap(BinaryHeap::new()),
+        starts,
+        successors,
+        |_| C::default(),
+        is_goal,
+    )
+}
+
+/// Like `dijkstra`, but explores states in order of cost plus `heuristic`.
+/// States are never reopened, so the heuristic must be consistent: it is
+/// zero at goals and drops by at most the cost of any single step. Merely
+/// never overestimating is not enough.
+pub fn astar<S, C, I, N, FN, FH, FG>(
+    starts: I,
+    successors: FN,
+    heuristic: FH,
+    is_goal: FG,
+) -> Option<Path<S, C>>
+where
+    S: Clone + Eq + Hash,
+    C: Copy + Ord + Add<Output = C> + Default,
+    I: IntoIterator<Item = S>,
+    FN: FnMut(&S) -> N,
+    N: IntoIterator<Item = (S, C)>,
+    FH: FnMut(&S) -> C,
+    FG: FnMut(&S) -> bool,
+{
+    search(
+        Heap(BinaryHeap::new()),
+        starts,
+        successors,
+        heuristic,
+        is_goal,
+    )
+}
+
+/// `dijkstra` backed by a bucket queue, for small non-negative integer costs.
+pub fn dijkstra_buckets<S, I, N, FN, FG>(
+    starts: I,
+    successors: FN,
+    is_goal: FG,
+) -> Option<Path<S, usize>>
+where
+    S: Clone + Eq + Hash,
+    I: IntoIterator<Item = S>,
+    FN: FnMut(&S) -> N,
+    N: IntoIterator<Item = (S, usize)>,
+    FG: FnMut(&S) -> bool,
+{
+    search(
+        Buckets {
+            buckets: vec![],
+            current: 0,
+        },
+        starts,
+        successors,
+        |_| 0,
+        is_goal,
+    )
+}
+
+/// Breadth-first distances from every point in `sources` to each reachable
+/// cell, only stepping onto cells accepted by `passable`.
+pub fn bfs<T, I, F>(
+    grid: &Grid<T>,
+    sources: I,
+    connectivity: Connectivity,
+    mut passable: F,
+) -> Grid<Option<usize>>
+where
+    I: IntoIterator<Item = Point>,
+    F: FnMut(Point, &T) -> bool,
+{
+    let mut distances = Grid::filled(grid.width(), grid.height(), None);
+    let mut queue = VecDeque::new();
+    for source in sources {
+        if distances.get(source) == Some(&None) {
+            distances[source] = Some(0);
+            queue.push_back(source);
+        }
+    }
+
+    while let Some(point) = queue.pop_front() {
+        let distance = distances[point].unwrap_or_default();
+        for (next, cell) in grid.neighbors(point, connectivity) {
+            if distances[next].is_none() && passable(next, cell) {
+                distances[next] = Some(distance + 1);
+                queue.push_back(next);
+            }
+        }
+    }
+
+    distances
+}
+
+/// Collects every cell connected to `start` through cells matching
+/// `predicate`, in the order they are reached.
+pub fn flood_fill<T, F>(
+    grid: &Grid<T>,
+    start: Point,
+    connectivity: Connectivity,
+    mut predicate: F,
+) -> Vec<Point>
+where
+    F: FnMut(Point, &T) -> bool,
+{
+    let mut visited = Grid::filled(grid.width(), grid.height(), false);
+    let mut filled = vec![];
+    if grid.get(start).is_some_and(|cell| predicate(start, cell)) {
+        visited[start] = true;
+        filled.push(start);
+    }
+
+    let mut index = 0;
+    while let Some(&point) = filled.get(index) {
+        for (next, cell) in grid.neighbors(point, connectivity) {
+            if !visited[next] && predicate(next, cell) {
+                visited[next] = true;
+                filled.push(next);
+            }
+        }
+        index += 1;
+    }
+
+    filled
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Region {
+    pub size: usize,
+    /// Top-left corner of the bounding box.
+    pub min: Point,
+    /// Bottom-right corner of the bounding box, inclusive.
+    pub max: Point,
+}
+
+#[derive(Debug)]
+pub struct Components {
+    /// The index into `regions` of each cell's component, `None` for cells
+    /// that weren't labelled.
+    pub labels: Grid<Option<usize>>,
+    pub regions: Vec<Region>,
+}
+
+fn label_components<T, FI, FJ>(
+    grid: &Grid<T>,
+    connectivity: Connectivity,
+    mut include: FI,
+    mut joined: FJ,
+) -> Components
+where
+    FI: FnMut(&T) -> bool,
+    FJ: FnMut(&T, &T) -> bool,
+{
+    let mut labels = Grid::filled(grid.width(), grid.height(), None);
+    let mut regions = vec![];
+    for (start, cell) in grid.iter_cells() {
+        if labels[start].is_some() || !include(cell) {
+            continue;
+        }
+
+        let label = regions.len();
+        let mut region = Region {
+            size: 0,
+            min: start,
+            max: start,
+        };
+        labels[start] = Some(label);
+        let mut stack = vec![start];
+        while let Some(point) = stack.pop() {
+            region.size += 1;
+            region.min = Point(region.min.0.min(point.0), region.min.1.min(point.1));
+            region.max = Point(region.max.0.max(point.0), region.max.1.max(point.1));
+            for (next, next_cell) in grid.neighbors(point, connectivity) {
+                if labels[next].is_none() && include(next_cell) && joined(&grid[point], next_cell) {
+                    labels[next] = Some(label);
+                    stack.push(next);
+                }
+            }
+        }
+        regions.push(region);
+    }
+
+    Components { labels, regions }
+}
+
+/// Labels groups of adjacent cells that all match `predicate`.
+pub fn connected_components<T, F>(
+    grid: &Grid<T>,
+    connectivity: Connectivity,
+    predicate: F,
+) -> Components
+where
+    F: FnMut(&T) -> bool,
+{
+    label_components(grid, connectivity, predicate, |_, _| true)
+}
+
+/// Labels every cell, grouping adjacent cells that hold equal values.
+pub fn connected_regions<T: PartialEq>(grid: &Grid<T>, connectivity: Connectivity) -> Components {
+    label_components(grid, connectivity, |_| true, |a, b| a == b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::SignedPoint;
+
+    fn graph_successors(node: &char) -> Vec<(char, u32)> {
+        match node {
+            'a' => vec![('b', 7), ('c', 9), ('f', 14)],
+            'b' => vec![('c', 10), ('d', 15)],
+            'c' => vec![('d', 11), ('f', 2)],
+            'd' => vec![('e', 6)],
+            'f' => vec![('e', 9)],
+            _ => vec![],
+        }
+    }
+
+    #[test]
+    fn test_dijkstra() {
+        let path = dijkstra(['a'], graph_successors, |n| *n == 'e').unwrap();
+        assert_eq!(path.cost, 20);
+        assert_eq!(path.states, vec!['a', 'c', 'f', 'e']);
+
+        let path = dijkstra(['b', 'f'], graph_successors, |n| *n == 'e').unwrap();
+        assert_eq!(path.cost, 9);
+        assert_eq!(path.states, vec!['f', 'e']);
+
+        assert_eq!(dijkstra(['e'], graph_successors, |n| *n == 'a'), None);
+    }
+
+    #[test]
+    fn test_grid_search() {
+        let grid = Grid::parse_with(
+            "..#....
+.##.##.
+...#...
+.#...#.",
+            |c| Some(c == '#'),
+        )
+        .unwrap();
+        let goal = Point(6, 0);
+        let successors = |point: &Point| {
+            point
+                .neighbors4(&grid)
+                .filter(|next| !grid[*next])
+                .map(|next| (next, 1))
+                .collect::<Vec<_>>()
+        };
+
+        let expected = dijkstra([Point(0, 0)], successors, |p| *p == goal).unwrap();
+        assert_eq!(expected.cost, 12);
+        assert_eq!(expected.states.len(), 13);
+
+        let path = astar(
+            [Point(0, 0)],
+            successors,
+            |p| SignedPoint::from(*p).manhattan_distance(goal.into()) as usize,
+            |p| *p == goal,
+        )
+        .unwrap();
+        assert_eq!(path.cost, expected.cost);
+        assert!(path
+            .states
+            .windows(2)
+            .all(|w| successors(&w[0]).iter().any(|(next, _)| *next == w[1])));
+
+        let path = dijkstra_buckets([Point(0, 0)], successors, |p| *p == goal).unwrap();
+        assert_eq!(path.cost, expected.cost);
+    }
+
+    const PIPE_MAZE: &str = "..........
+.S------7.
+.|F----7|.
+.||OOOO||.
+.||OOOO||.
+.|L-7F-J|.
+.|II||II|.
+.L--JL--J.
+..........";
+
+    #[test]
+    fn test_bfs() {
+        let grid = PIPE_MAZE.parse::<Grid<char>>().unwrap();
+        let distances = bfs(&grid, [Point(1, 1)], Connectivity::Four, |_, c| {
+            !matches!(c, '.' | 'O' | 'I')
+        });
+        assert_eq!(distances[Point(1, 1)], Some(0));
+        assert_eq!(distances[Point(8, 7)], Some(13));
+        assert_eq!(distances[Point(3, 2)], Some(3));
+        assert_eq!(distances[Point(5, 6)], Some(9));
+        assert_eq!(distances[Point(0, 0)], None);
+
+        let from_corners = bfs(
+            &grid,
+            [Point(0, 0), Point(9, 8)],
+            Connectivity::Eight,
+            |_, c| *c == '.',
+        );
+        assert_eq!(from_corners[Point(9, 0)], Some(8));
+        assert_eq!(from_corners[Point(0, 8)], Some(8));
+        assert_eq!(from_corners[Point(4, 4)], None);
+    }
+
+    #[test]
+    fn test_flood_fill() {
+        let grid = PIPE_MAZE.parse::<Grid<char>>().unwrap();
+        let outside = flood_fill(&grid, Point(0, 0), Connectivity::Four, |_, c| *c == '.');
+        assert_eq!(outside.len(), 34);
+        assert_eq!(outside[0], Point(0, 0));
+
+        let inside = flood_fill(&grid, Point(3, 3), Connectivity::Four, |_, c| *c == 'O');
+        assert_eq!(inside.len(), 8);
+        assert!(flood_fill(&grid, Point(1, 1), Connectivity::Four, |_, c| *c == '.').is_empty());
+    }
+
+    #[test]
+    fn test_connected_components() {
+        let grid = PIPE_MAZE.parse::<Grid<char>>().unwrap();
+        let enclosed = connected_components(&grid, Connectivity::Four, |c| *c == 'I');
+        assert_eq!(enclosed.regions.len(), 2);
+        assert_eq!(
+            enclosed.regions[0],
+            Region {
+                size: 2,
+                min: Point(2, 6),
+                max: Point(3, 6)
+            }
+        );
+        assert_eq!(enclosed.labels[Point(7, 6)], Some(1));
+        assert_eq!(enclosed.labels[Point(5, 6)], None);
+
+        let grid = "#..#\n.#..\n...#".parse::<Grid<char>>().unwrap();
+        assert_eq!(
+            connected_components(&grid, Connectivity::Four, |c| *c == '#')
+                .regions
+                .len(),
+            4
+        );
+        let diagonal = connected_components(&grid, Connectivity::Eight, |c| *c == '#');
+        assert_eq!(diagonal.regions.len(), 3);
+        assert_eq!(diagonal.regions[0].size, 2);
+    }
+
+    #[test]
+    fn test_connected_regions() {
+        let grid = "AAAA\nBBCD\nBBCC\nEEEC".parse::<Grid<char>>().unwrap();
+        let components = connected_regions(&grid, Connectivity::Four);
+        let sizes = components
+            .regions
+            .iter()
+            .map(|region| region.size)
+            .collect::<Vec<_>>();
+        assert_eq!(sizes, vec![4, 4, 4, 1, 3]);
+        assert_eq!(
+            components.regions[2],
+            Region {
+                size: 4,
+                min: Point(2, 1),
+                max: Point(3, 3)
+            }
+        );
+        assert!(components
+            .labels
+            .iter_cells()
+            .all(|(_, label)| label.is_some()));
+    }
+}
@@ -0,0 +1,240 @@
+use crate::grid::{Grid, Point, SignedPoint};
+use std::collections::HashMap;
+
+/// Inclusive bounding box of a set of points.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Bounds {
+    pub min: SignedPoint,
+    pub max: SignedPoint,
+}
+
+impl Bounds {
+    fn around(point: SignedPoint) -> Self {
+        Self {
+            min: point,
+            max: point,
+        }
+    }
+
+    fn extend(&mut self, point: SignedPoint) {
+        self.min = SignedPoint(self.min.0.min(point.0), self.min.1.min(point.1));
+        self.max = SignedPoint(self.max.0.max(point.0), self.max.1.max(point.1));
+    }
+
+    pub fn width(&self) -> u64 {
+        self.min.0.abs_diff(self.max.0) + 1
+    }
+
+    pub fn height(&self) -> u64 {
+        self.min.1.abs_diff(self.max.1) + 1
+    }
+
+    pub fn area(&self) -> u128 {
+        self.width() as u128 * self.height() as u128
+    }
+
+    pub fn contains(&self, point: SignedPoint) -> bool {
+        (self.min.0..=self.max.0).contains(&point.0) && (self.min.1..=self.max.1).contains(&point.1)
+    }
+}
+
+/// A grid that only stores occupied cells, for maps whose coordinates are
+/// too large or unbounded for a dense `Grid`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SparseGrid<T> {
+    cells: HashMap<SignedPoint, T>,
+    bounds: Option<Bounds>,
+}
+
+impl<T> Default for SparseGrid<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> SparseGrid<T> {
+    pub fn new() -> Self {
+        Self {
+            cells: HashMap::new(),
+            bounds: None,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    /// Smallest box holding every occupied cell, `None` while empty.
+    pub fn bounds(&self) -> Option<Bounds> {
+        self.bounds
+    }
+
+    pub fn get(&self, point: SignedPoint) -> Option<&T> {
+        self.cells.get(&point)
+    }
+
+    pub fn get_mut(&mut self, point: SignedPoint) -> Option<&mut T> {
+        self.cells.get_mut(&point)
+    }
+
+    pub fn contains(&self, point: SignedPoint) -> bool {
+        self.cells.contains_key(&point)
+    }
+
+    pub fn insert(&mut self, point: SignedPoint, value: T) -> Option<T> {
+        match &mut self.bounds {
+            Some(bounds) => bounds.extend(point),
+            None => self.bounds = Some(Bounds::around(point)),
+        }
+        self.cells.insert(point, value)
+    }
+
+    pub fn remove(&mut self, point: SignedPoint) -> Option<T> {
+        let value = self.cells.remove(&point)?;
+        let on_edge = self.bounds.is_some_and(|b| {
+            point.0 == b.min.0 || point.0 == b.max.0 || point.1 == b.min.1 || point.1 == b.max.1
+        });
+        if on_edge {
+            self.bounds = self.cells.keys().fold(None, |bounds, point| match bounds {
+                Some(mut bounds) => {
+                    bounds.extend(*point);
+                    Some(bounds)
+                }
+                None => Some(Bounds::around(*point)),
+            });
+        }
+        Some(value)
+    }
+
+    /// Occupied cells ordered by row, then column.
+    pub fn iter(&self) -> impl Iterator<Item = (SignedPoint, &T)> {
+        let mut cells = self.cells.iter().collect::<Vec<_>>();
+        cells.sort_unstable_by_key(|(point, _)| (point.1, point.0));
+        cells.into_iter().map(|(point, value)| (*point, value))
+    }
+
+    /// Draws the bounding box, using `empty` for unoccupied cells.
+    pub fn render<F>(&self, empty: char, format: F) -> String
+    where
+        F: Fn(&T) -> char,
+    {
+        let Some(bounds) = self.bounds else {
+            return String::new();
+        };
+        (bounds.min.1..=bounds.max.1)
+            .map(|y| {
+                (bounds.min.0..=bounds.max.0)
+                    .map(|x| self.get(SignedPoint(x, y)).map_or(empty, &format))
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Copies the bounding box into a dense grid whose origin is
+    /// `bounds().min`, unless it would have more than `max_cells` cells.
+    pub fn to_grid(&self, max_cells: usize) -> Option<Grid<Option<T>>>
+    where
+        T: Clone,
+    {
+        let bounds = self.bounds?;
+        if bounds.area() > max_cells as u128 {
+            return None;
+        }
+        let (width, height) = (bounds.width() as usize, bounds.height() as usize);
+        let mut grid = Grid::filled(width, height, None);
+        self.cells.iter().for_each(|(point, value)| {
+            let offset = *point - bounds.min;
+            grid.set(
+                Point(offset.0 as usize, offset.1 as usize),
+                Some(value.clone()),
+            );
+        });
+        Some(grid)
+    }
+}
+
+impl<T> FromIterator<(SignedPoint, T)> for SparseGrid<T> {
+    fn from_iter<I: IntoIterator<Item = (SignedPoint, T)>>(iter: I) -> Self {
+        let mut grid = Self::new();
+        iter.into_iter().for_each(|(point, value)| {
+            grid.insert(point, value);
+        });
+        grid
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bounds() {
+        let mut grid = SparseGrid::new();
+        assert_eq!(grid.bounds(), None);
+        grid.insert(SignedPoint(3, -2), 'a');
+        grid.insert(SignedPoint(-1_000_000_000_000, 7), 'b');
+        grid.insert(SignedPoint(0, 0), 'c');
+        let bounds = grid.bounds().unwrap();
+        assert_eq!(bounds.min, SignedPoint(-1_000_000_000_000, -2));
+        assert_eq!(bounds.max, SignedPoint(3, 7));
+        assert_eq!(bounds.width(), 1_000_000_000_004);
+        assert_eq!(bounds.area(), 10_000_000_000_040);
+        assert!(bounds.contains(SignedPoint(0, 7)));
+        assert!(!bounds.contains(SignedPoint(0, 8)));
+        assert_eq!(grid.to_grid(1_000_000), None);
+
+        assert_eq!(grid.remove(SignedPoint(-1_000_000_000_000, 7)), Some('b'));
+        assert_eq!(grid.remove(SignedPoint(5, 5)), None);
+        let bounds = grid.bounds().unwrap();
+        assert_eq!(
+            (bounds.min, bounds.max),
+            (SignedPoint(0, -2), SignedPoint(3, 0))
+        );
+
+        grid.remove(SignedPoint(0, 0));
+        grid.remove(SignedPoint(3, -2));
+        assert!(grid.is_empty());
+        assert_eq!(grid.bounds(), None);
+    }
+
+    #[test]
+    fn test_iter_and_render() {
+        let grid = [(2, 1), (-1, 0), (0, 1), (1, -1)]
+            .into_iter()
+            .enumerate()
+            .map(|(i, (x, y))| (SignedPoint(x, y), i))
+            .collect::<SparseGrid<_>>();
+        assert_eq!(
+            grid.iter().map(|(p, v)| (p, *v)).collect::<Vec<_>>(),
+            vec![
+                (SignedPoint(1, -1), 3),
+                (SignedPoint(-1, 0), 1),
+                (SignedPoint(0, 1), 2),
+                (SignedPoint(2, 1), 0)
+            ]
+        );
+        assert_eq!(
+            grid.render('.', |v| char::from_digit(*v as u32, 10).unwrap()),
+            "..3.\n1...\n.2.0"
+        );
+        assert_eq!(SparseGrid::<()>::new().render('.', |_| '#'), "");
+    }
+
+    #[test]
+    fn test_to_grid() {
+        let grid = [(SignedPoint(-2, -1), 'x'), (SignedPoint(0, 0), 'y')]
+            .into_iter()
+            .collect::<SparseGrid<_>>();
+        let dense = grid.to_grid(6).unwrap();
+        assert_eq!((dense.width(), dense.height()), (3, 2));
+        assert_eq!(dense[Point(0, 0)], Some('x'));
+        assert_eq!(dense[Point(2, 1)], Some('y'));
+        assert_eq!(dense[Point(1, 1)], None);
+        assert_eq!(grid.to_grid(5), None);
+    }
+}